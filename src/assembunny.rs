@@ -1,29 +1,89 @@
+use std::collections::VecDeque;
+
 use itertools::Itertools;
 use thiserror::Error;
 
-type Register = char;
+mod assembler;
+mod clock;
+mod compile;
+mod cycle;
+mod debugger;
+mod decompile;
+mod differential;
+mod flow;
+mod history;
+mod io;
+mod lint;
+mod network;
+mod optimize;
+mod profile;
+mod snapshot;
+pub use assembler::{assemble, assemble_with, disassemble, AssembleError, AssemblyError};
+pub use clock::{analyze_clock, Clock, ClockError, Unfollowable};
+pub use compile::Backend;
+use compile::Op;
+pub use cycle::Cycle;
+pub use debugger::{Debugger, Event};
+pub use decompile::{decompile, lift, Expr, Stmt, Until};
+pub use differential::{compare, differential, random_program, Divergence, Engine, Outcome};
+pub use flow::{Block, Edge, EdgeKind, FlowGraph, NaturalLoop, Target};
+pub use history::Undo;
+pub use io::{Pattern, Reader, Sink, Source, Writer};
+pub use lint::{lint, lint_source, Lint, Warning};
+pub use network::{Network, NetworkStop};
+use optimize::Fused;
+pub use profile::{Loop, Profile};
+pub use snapshot::{Snapshot, SnapshotParseError};
+
+pub type Register = char;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Value {
     Literal(i32),
     Register(char),
 }
 
-#[derive(Debug, Error)]
-pub enum ValueParseError {}
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ValueParseError {
+    #[error("unknown register `{0}`")]
+    UnknownRegister(String),
+
+    #[error("bad literal `{0}`")]
+    BadLiteral(String),
+}
+
+impl Value {
+    // Parse with `registers` as the only register names
+    pub fn parse_with(s: &str, registers: &[Register]) -> Result<Self, ValueParseError> {
+        if let Ok(value) = s.parse::<i32>() {
+            return Ok(Value::Literal(value));
+        }
+        match s.chars().collect_vec()[..] {
+            [c] if registers.contains(&c) => Ok(Value::Register(c)),
+            ['-' | '+' | '0'..='9', ..] => Err(ValueParseError::BadLiteral(s.to_string())),
+            _ => Err(ValueParseError::UnknownRegister(s.to_string())),
+        }
+    }
+}
+
 impl std::str::FromStr for Value {
     type Err = ValueParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(value) = s.parse::<i32>() {
-            Ok(Value::Literal(value))
-        } else {
-            Ok(Value::Register(s.chars().next().unwrap()))
+        Value::parse_with(s, &REGISTERS)
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Literal(v) => write!(f, "{v}"),
+            Value::Register(r) => write!(f, "{r}"),
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Instruction {
     Cpy(Value, Value),
     Inc(Value),
@@ -31,133 +91,586 @@ pub enum Instruction {
     Jnz(Value, Value),
     Tgl(Value),
     Out(Value),
+    // Only in a dialect with `receive`
+    Rcv(Value),
+    // Only in the extended dialect
+    Add(Value, Value),
+    Mul(Value, Value),
+    Nop,
+}
+
+impl Instruction {
+    pub fn opcode(&self) -> &'static str {
+        match self {
+            Instruction::Cpy(..) => "cpy",
+            Instruction::Inc(_) => "inc",
+            Instruction::Dec(_) => "dec",
+            Instruction::Jnz(..) => "jnz",
+            Instruction::Tgl(_) => "tgl",
+            Instruction::Out(_) => "out",
+            Instruction::Rcv(_) => "rcv",
+            Instruction::Add(..) => "add",
+            Instruction::Mul(..) => "mul",
+            Instruction::Nop => "nop",
+        }
+    }
+
+    // What a `tgl` aimed at this instruction rewrites it to.  The extended
+    // instructions follow the puzzle's rules: `add` and `mul` take two
+    // operands so become `jnz`, and `nop` has nothing to toggle.
+    pub fn toggled(self) -> Self {
+        match self {
+            Instruction::Inc(a) => Instruction::Dec(a),
+            Instruction::Dec(a) => Instruction::Inc(a),
+            Instruction::Tgl(a) => Instruction::Inc(a),
+            Instruction::Out(a) => Instruction::Inc(a),
+            Instruction::Rcv(a) => Instruction::Inc(a),
+            Instruction::Jnz(a, b) => Instruction::Cpy(a, b),
+            Instruction::Cpy(a, b) | Instruction::Add(a, b) | Instruction::Mul(a, b) => {
+                Instruction::Jnz(a, b)
+            }
+            Instruction::Nop => Instruction::Nop,
+        }
+    }
 }
 
-#[derive(Error, Debug)]
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Cpy(a, b) => write!(f, "cpy {a} {b}"),
+            Instruction::Inc(a) => write!(f, "inc {a}"),
+            Instruction::Dec(a) => write!(f, "dec {a}"),
+            Instruction::Jnz(a, b) => write!(f, "jnz {a} {b}"),
+            Instruction::Tgl(a) => write!(f, "tgl {a}"),
+            Instruction::Out(a) => write!(f, "out {a}"),
+            Instruction::Rcv(a) => write!(f, "rcv {a}"),
+            Instruction::Add(a, b) => write!(f, "add {a} {b}"),
+            Instruction::Mul(a, b) => write!(f, "mul {a} {b}"),
+            Instruction::Nop => write!(f, "nop"),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
 pub enum InstructionParseError {
-    #[error("unknown instruction")]
+    #[error("missing instruction")]
+    Empty,
+
+    #[error("unknown instruction `{0}`")]
     Unknown(String),
 
-    #[error("Number Parse")]
-    Number(#[from] std::num::ParseIntError),
+    #[error("`{0}` is missing an operand")]
+    MissingOperand(String),
+
+    #[error("extra operand `{0}`")]
+    ExtraOperand(String),
 
-    #[error("Value Parse")]
+    #[error(transparent)]
     Value(#[from] ValueParseError),
 }
 
+// What a listing may contain beyond the puzzle's own instruction set
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dialect {
+    pub registers: Vec<Register>,
+    // `rcv x` waits for a value sent by another machine
+    pub receive: bool,
+    // `add x y` and `mul x y` update y by x, and `nop` does nothing
+    pub extended: bool,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect {
+            registers: REGISTERS.to_vec(),
+            receive: false,
+            extended: false,
+        }
+    }
+}
+
+impl Dialect {
+    // Everything on top of the puzzle's instructions, with `rcv` for input
+    pub fn extended() -> Self {
+        Dialect {
+            receive: true,
+            extended: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl Instruction {
+    pub fn parse_with(s: &str, dialect: &Dialect) -> Result<Self, InstructionParseError> {
+        let toks = s.split_ascii_whitespace().collect_vec();
+        let (&op, args) = toks.split_first().ok_or(InstructionParseError::Empty)?;
+        let arity = match op {
+            "cpy" | "jnz" => 2,
+            "inc" | "dec" | "tgl" | "out" => 1,
+            "rcv" if dialect.receive => 1,
+            "add" | "mul" if dialect.extended => 2,
+            "nop" if dialect.extended => 0,
+            _ => return Err(InstructionParseError::Unknown(op.to_string())),
+        };
+        if args.len() < arity {
+            return Err(InstructionParseError::MissingOperand(op.to_string()));
+        }
+        if let Some(extra) = args.get(arity) {
+            return Err(InstructionParseError::ExtraOperand(extra.to_string()));
+        }
+        let arg = |i: usize| Value::parse_with(args[i], &dialect.registers);
+        match op {
+            "cpy" => Ok(Instruction::Cpy(arg(0)?, arg(1)?)),
+            "inc" => Ok(Instruction::Inc(arg(0)?)),
+            "dec" => Ok(Instruction::Dec(arg(0)?)),
+            "jnz" => Ok(Instruction::Jnz(arg(0)?, arg(1)?)),
+            "tgl" => Ok(Instruction::Tgl(arg(0)?)),
+            "out" => Ok(Instruction::Out(arg(0)?)),
+            "rcv" => Ok(Instruction::Rcv(arg(0)?)),
+            "add" => Ok(Instruction::Add(arg(0)?, arg(1)?)),
+            "mul" => Ok(Instruction::Mul(arg(0)?, arg(1)?)),
+            "nop" => Ok(Instruction::Nop),
+            _ => unreachable!(),
+        }
+    }
+}
+
 impl std::str::FromStr for Instruction {
     type Err = InstructionParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let toks = s.split_ascii_whitespace().collect_vec();
-        match toks[0] {
-            "cpy" => Ok(Instruction::Cpy(toks[1].parse()?, toks[2].parse()?)),
-            "inc" => Ok(Instruction::Inc(toks[1].parse()?)),
-            "dec" => Ok(Instruction::Dec(toks[1].parse()?)),
-            "jnz" => Ok(Instruction::Jnz(toks[1].parse()?, toks[2].parse()?)),
-            "tgl" => Ok(Instruction::Tgl(toks[1].parse()?)),
-            "out" => Ok(Instruction::Out(toks[1].parse()?)),
-            _ => Err(InstructionParseError::Unknown(s.to_string())),
+        Instruction::parse_with(s, &Dialect::default())
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("line {line}: {error}")]
+pub struct LineError<E = InstructionParseError> {
+    pub line: usize,
+    pub error: E,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("{}", .0.iter().join("\n"))]
+pub struct ProgramParseError(pub Vec<LineError>);
+
+pub struct Program;
+
+impl Program {
+    // Parse a whole listing, collecting an error for every bad line rather
+    // than stopping at the first one.
+    pub fn parse(input: &str) -> Result<Vec<Instruction>, ProgramParseError> {
+        Program::parse_with(input, &Dialect::default())
+    }
+
+    pub fn parse_with(
+        input: &str,
+        dialect: &Dialect,
+    ) -> Result<Vec<Instruction>, ProgramParseError> {
+        let mut program = vec![];
+        let mut errors = vec![];
+        for (line, text) in input.lines().enumerate() {
+            match Instruction::parse_with(text, dialect) {
+                Ok(instruction) => program.push(instruction),
+                Err(error) => errors.push(LineError {
+                    line: line + 1,
+                    error,
+                }),
+            }
+        }
+        if errors.is_empty() {
+            Ok(program)
+        } else {
+            Err(ProgramParseError(errors))
+        }
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let err = Program::parse("cpy 1 a\ncpy 1\ninc e\njnz a 2 3\nmul a b\ndec 1x").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 2: `cpy` is missing an operand\n\
+         line 3: unknown register `e`\n\
+         line 4: extra operand `3`\n\
+         line 5: unknown instruction `mul`\n\
+         line 6: bad literal `1x`"
+    );
+}
+
+pub const REGISTERS: [Register; 4] = ['a', 'b', 'c', 'd'];
+
+// What happens when a register outgrows its width
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Arithmetic {
+    // Wrap at 32 bits
+    #[default]
+    I32,
+    // Wrap at 64 bits
+    I64,
+    // 64 bits, and overflowing is a fault
+    Checked,
+}
+
+impl Arithmetic {
+    fn fit(self, value: i128) -> Option<i64> {
+        match self {
+            Arithmetic::I32 => Some(value as i32 as i64),
+            Arithmetic::I64 => Some(value as i64),
+            Arithmetic::Checked => i64::try_from(value).ok(),
         }
     }
 }
 
-#[derive(Default)]
+#[derive(Error, Clone, Copy, Debug, PartialEq, Eq)]
+#[error("unknown register `{0}`")]
+pub struct UnknownRegister(pub Register);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    NegativePc(i32),
+    UnknownRegister(Register),
+    // Checked arithmetic overflowed at this pc
+    Overflow(i32),
+}
+
+impl From<UnknownRegister> for Fault {
+    fn from(e: UnknownRegister) -> Self {
+        Fault::UnknownRegister(e.0)
+    }
+}
+
+// Why a budgeted run came back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    Output(i64),
+    OutOfFuel,
+    Fault(Fault),
+    // Waiting on a `rcv` with nothing sent
+    Blocked,
+}
+
+#[derive(Clone, Default)]
 pub struct Cpu {
-    a: i32,
-    b: i32,
-    c: i32,
-    d: i32,
+    names: Vec<Register>,
+    registers: Vec<i64>,
+    arithmetic: Arithmetic,
     pc: i32,
     program: Vec<Instruction>,
-    output: Option<i32>,
+    output: Option<i64>,
+    inbox: VecDeque<i64>,
+    fault: Option<Fault>,
+    optimized: bool,
+    fused: Vec<Option<Fused>>,
+    // The compiled program, when that backend is in use
+    ops: Option<Vec<Op>>,
+    profile: Option<Profile>,
+    // Undo information for each tick, when recording
+    history: Option<Vec<Undo>>,
 }
 
 impl Cpu {
     pub fn new(program: Vec<Instruction>) -> Self {
+        Cpu::with_registers(program, &REGISTERS)
+    }
+
+    pub fn with_registers(program: Vec<Instruction>, names: &[Register]) -> Self {
         Cpu {
+            names: names.to_vec(),
+            registers: vec![0; names.len()],
             program,
             ..Default::default()
         }
     }
-    pub fn get(&self, src: Register) -> i32 {
-        match src {
-            'a' => self.a,
-            'b' => self.b,
-            'c' => self.c,
-            'd' => self.d,
-            _ => unreachable!(),
-        }
+
+    pub fn set_arithmetic(&mut self, arithmetic: Arithmetic) {
+        self.arithmetic = arithmetic;
     }
 
-    pub fn set(&mut self, dest: Register, value: i32) {
-        match dest {
-            'a' => self.a = value,
-            'b' => self.b = value,
-            'c' => self.c = value,
-            'd' => self.d = value,
-            _ => unreachable!(),
+    // Run the add and multiply loops as single operations.  Toggles rebuild
+    // the table, so a rewritten loop falls back to plain interpretation.
+    pub fn optimize(&mut self) {
+        self.optimized = true;
+        self.fused = optimize::optimize(&self.program);
+    }
+
+    // Start counting executions, jumps and toggles from here on
+    pub fn enable_profiling(&mut self) {
+        self.profile = Some(Profile::new(self.program.len()));
+    }
+
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    pub fn pc(&self) -> i32 {
+        self.pc
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    // Patch the instruction at `addr`, or add one when `addr` is just past the
+    // end.  False, and no change, for anywhere further out.  A patch can be
+    // the fix for a fault, so clears it.
+    pub fn poke(&mut self, addr: usize, instruction: Instruction) -> bool {
+        match addr.cmp(&self.program.len()) {
+            std::cmp::Ordering::Less => self.program[addr] = instruction,
+            std::cmp::Ordering::Equal => self.program.push(instruction),
+            std::cmp::Ordering::Greater => return false,
         }
+        self.fault = None;
+        if let Some(profile) = &mut self.profile {
+            profile.resize(self.program.len());
+        }
+        self.restart_history();
+        if self.optimized {
+            self.optimize();
+        }
+        if self.ops.is_some() {
+            self.set_backend(Backend::Compiled);
+        }
+        true
+    }
+
+    // Why the last run stopped short, if it did
+    pub fn fault(&self) -> Option<Fault> {
+        self.fault
+    }
+
+    pub fn registers(&self) -> impl Iterator<Item = (Register, i64)> + '_ {
+        self.names
+            .iter()
+            .copied()
+            .zip(self.registers.iter().copied())
+    }
+
+    fn slot(&self, r: Register) -> Result<usize, UnknownRegister> {
+        self.names
+            .iter()
+            .position(|&n| n == r)
+            .ok_or(UnknownRegister(r))
+    }
+
+    pub fn get(&self, src: Register) -> Result<i64, UnknownRegister> {
+        Ok(self.registers[self.slot(src)?])
+    }
+
+    // Not part of any tick, so recorded history from before can't be undone
+    // past it
+    pub fn set(&mut self, dest: Register, value: i64) -> Result<(), UnknownRegister> {
+        let slot = self.slot(dest)?;
+        self.registers[slot] = self.arithmetic.fit(value.into()).unwrap_or(value);
+        self.restart_history();
+        Ok(())
     }
 
-    fn eval(&self, value: Value) -> i32 {
+    fn eval(&self, value: Value) -> Result<i64, Fault> {
         match value {
-            Value::Register(c) => self.get(c),
-            Value::Literal(v) => v,
+            Value::Register(c) => Ok(self.get(c)?),
+            Value::Literal(v) => Ok(v.into()),
+        }
+    }
+
+    fn write(&mut self, dest: Register, value: i128) -> Result<(), Fault> {
+        let value = self.arithmetic.fit(value).ok_or(Fault::Overflow(self.pc))?;
+        self.assign(self.slot(dest)?, value);
+        Ok(())
+    }
+
+    // A whole loop at once, unless checked arithmetic would overflow part
+    // way round.  Then it only goes as many times round as fit, and leaves
+    // the plain instructions to fault exactly where they would.
+    fn fused(&mut self, op: Fused) -> Result<bool, Fault> {
+        let (dest, counter, step) = match op {
+            Fused::Add { dest, counter } => (dest, counter, 1),
+            Fused::Mul {
+                dest, src, counter, ..
+            } => (dest, counter, self.eval(src)?),
+        };
+        let (start, rounds) = (self.get(dest)? as i128, self.get(counter)?);
+        if step <= 0 || rounds <= 0 {
+            return Ok(false);
         }
+        let whole = match self.arithmetic.fit(start + step as i128 * rounds as i128) {
+            Some(_) => rounds,
+            None => ((i64::MAX as i128 - start) / step as i128) as i64,
+        };
+        if whole == 0 {
+            return Ok(false);
+        }
+        self.write(dest, start + step as i128 * whole as i128)?;
+        if let Fused::Mul { scratch, .. } = op {
+            self.write(scratch, 0)?;
+        }
+        self.write(counter, (rounds - whole).into())?;
+        if whole == rounds {
+            self.pc += op.len();
+        }
+        Ok(true)
     }
 
     fn tick(&mut self) {
+        if let Err(fault) = self.try_tick() {
+            self.fault = Some(fault);
+        }
+    }
+
+    fn try_tick(&mut self) -> Result<(), Fault> {
+        let pc = self.pc;
+        self.record_tick(pc);
+        if pc < 0 {
+            return Err(Fault::NegativePc(pc));
+        }
+        if let Some(&Some(op)) = self.fused.get(pc as usize) {
+            if self.fused(op)? {
+                if let Some(profile) = &mut self.profile {
+                    profile.record(pc, op.opcode());
+                    // The whole loop ran at once, so its closing jump shows
+                    // up as a single iteration
+                    profile.jump(pc + op.len() - 1, Some(pc));
+                }
+                return Ok(());
+            }
+        }
+        let instruction = self.program[pc as usize];
+        if let Some(profile) = &mut self.profile {
+            profile.record(pc, instruction.opcode());
+        }
+        if let Some(ops) = &self.ops {
+            return self.exec(pc, ops[pc as usize]);
+        }
         let mut next = 1;
-        match self.program[self.pc as usize] {
-            Instruction::Cpy(src, Value::Register(dest)) => self.set(dest, self.eval(src)),
+        match instruction {
+            Instruction::Cpy(src, Value::Register(dest)) => {
+                self.write(dest, self.eval(src)?.into())?
+            }
             Instruction::Cpy(_, Value::Literal(_)) => (),
-            Instruction::Inc(Value::Register(dest)) => self.set(dest, self.get(dest) + 1),
+            Instruction::Inc(Value::Register(dest)) => {
+                self.write(dest, self.get(dest)? as i128 + 1)?
+            }
             Instruction::Inc(Value::Literal(_)) => (),
-            Instruction::Dec(Value::Register(dest)) => self.set(dest, self.get(dest) - 1),
+            Instruction::Dec(Value::Register(dest)) => {
+                self.write(dest, self.get(dest)? as i128 - 1)?
+            }
             Instruction::Dec(Value::Literal(_)) => (),
             Instruction::Jnz(test, jump) => {
-                if self.eval(test) != 0 {
-                    next = self.eval(jump);
+                let taken = self.eval(test)? != 0;
+                if taken {
+                    // Anything out of range halts or faults all the same
+                    next = self.eval(jump)?.clamp(i32::MIN.into(), i32::MAX.into()) as i32;
+                }
+                if let Some(profile) = &mut self.profile {
+                    profile.jump(pc, taken.then_some(pc.saturating_add(next)));
                 }
             }
             Instruction::Tgl(Value::Register(dest)) => {
-                let addr = self.pc + self.get(dest);
-                if addr >= 0 && addr < self.program.len() as i32 {
-                    self.program[addr as usize] = match self.program[addr as usize] {
-                        Instruction::Inc(a) => Instruction::Dec(a),
-                        Instruction::Dec(a) => Instruction::Inc(a),
-                        Instruction::Tgl(a) => Instruction::Inc(a),
-                        Instruction::Out(a) => Instruction::Inc(a),
-                        Instruction::Jnz(a, b) => Instruction::Cpy(a, b),
-                        Instruction::Cpy(a, b) => Instruction::Jnz(a, b),
-                    };
-                }
+                self.toggle((pc as i64).saturating_add(self.get(dest)?))
             }
             Instruction::Tgl(Value::Literal(_)) => (),
-            Instruction::Out(src) => self.output = Some(self.eval(src)),
+            Instruction::Out(src) => self.output = Some(self.eval(src)?),
+            Instruction::Rcv(Value::Register(dest)) => match self.receive() {
+                Some(v) => self.write(dest, v.into())?,
+                None => next = 0,
+            },
+            Instruction::Rcv(Value::Literal(_)) => (),
+            Instruction::Add(src, Value::Register(dest)) => {
+                self.write(dest, self.get(dest)? as i128 + self.eval(src)? as i128)?
+            }
+            Instruction::Mul(src, Value::Register(dest)) => {
+                self.write(dest, self.get(dest)? as i128 * self.eval(src)? as i128)?
+            }
+            Instruction::Add(_, Value::Literal(_))
+            | Instruction::Mul(_, Value::Literal(_))
+            | Instruction::Nop => (),
         }
-        self.pc += next;
+        self.pc = pc.saturating_add(next);
+        Ok(())
     }
 
+    fn toggle(&mut self, addr: i64) {
+        if addr < 0 || addr >= self.program.len() as i64 {
+            return;
+        }
+        let addr = addr as usize;
+        self.record_toggle(addr, self.program[addr]);
+        self.program[addr] = self.program[addr].toggled();
+        if let Some(profile) = &mut self.profile {
+            profile.toggle(addr);
+        }
+        if self.optimized {
+            self.fused = optimize::optimize(&self.program);
+        }
+        if self.ops.is_some() {
+            self.recompile(addr);
+        }
+    }
+
+    // Off the end of the program, or stuck on a fault
     pub fn halted(&self) -> bool {
-        self.pc >= self.program.len() as i32
+        self.fault.is_some() || self.pc >= self.program.len() as i32
+    }
+
+    // Queue a value for `rcv`
+    pub fn send(&mut self, value: i64) {
+        self.inbox.push_back(value);
+    }
+
+    // Stuck on a `rcv` until something is sent
+    pub fn waiting(&self) -> bool {
+        self.inbox.is_empty()
+            && matches!(
+                self.program.get(self.pc as usize),
+                Some(Instruction::Rcv(Value::Register(_)))
+            )
     }
 
     pub fn run(&mut self) {
-        while !self.halted() {
+        while !self.halted() && !self.waiting() {
             self.tick();
         }
     }
 
-    pub fn run_till_output(&mut self) -> Option<i32> {
+    pub fn run_till_output(&mut self) -> Option<i64> {
         self.output = None;
-        while !self.halted() && self.output == None {
+        while !self.halted() && !self.waiting() && self.output.is_none() {
             self.tick();
         }
         self.output
     }
 
+    // Like `run_till_output`, but each tick (a fused loop counts as one)
+    // burns a unit of `fuel`, and a bad jump is reported rather than panicking.
+    // Fuel left over can be passed to the next call.
+    pub fn run_with_fuel(&mut self, fuel: &mut u64) -> Stop {
+        self.output = None;
+        loop {
+            if let Some(fault) = self.fault {
+                return Stop::Fault(fault);
+            }
+            if self.halted() {
+                return Stop::Halted;
+            }
+            if self.pc < 0 {
+                return Stop::Fault(Fault::NegativePc(self.pc));
+            }
+            if self.waiting() {
+                return Stop::Blocked;
+            }
+            if *fuel == 0 {
+                return Stop::OutOfFuel;
+            }
+            *fuel -= 1;
+            self.tick();
+            if let Some(v) = self.output {
+                return Stop::Output(v);
+            }
+        }
+    }
+
     pub fn iter(self) -> CpuIter {
         CpuIter { cpu: self }
     }
@@ -168,9 +681,216 @@ pub struct CpuIter {
 }
 
 impl Iterator for CpuIter {
-    type Item = i32;
+    type Item = i64;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.cpu.run_till_output()
     }
 }
+
+// A listing for tests, in the widest dialect so any of them parse
+#[cfg(test)]
+pub(crate) fn parse(input: &str) -> Vec<Instruction> {
+    Program::parse_with(input, &Dialect::extended()).unwrap()
+}
+
+// Day 23's program, ready to run on the example's 7
+#[cfg(test)]
+pub(crate) fn day23() -> Cpu {
+    let mut cpu = Cpu::new(parse(include_str!("../input/2016/day23.txt")));
+    cpu.set('a', 7).unwrap();
+    cpu
+}
+
+#[cfg(test)]
+fn run_both(program: &[Instruction], a: i64, c: i64) {
+    for arithmetic in [Arithmetic::I32, Arithmetic::Checked] {
+        let mut plain = Cpu::new(program.to_vec());
+        let mut fast = Cpu::new(program.to_vec());
+        fast.optimize();
+        for cpu in [&mut plain, &mut fast] {
+            cpu.set_arithmetic(arithmetic);
+            cpu.set('a', a).unwrap();
+            cpu.set('c', c).unwrap();
+            cpu.run();
+        }
+        assert!(plain.registers().eq(fast.registers()));
+        assert_eq!((plain.pc, plain.fault), (fast.pc, fast.fault));
+    }
+}
+
+#[test]
+fn test_optimize_matches_plain() {
+    run_both(&parse(include_str!("../input/2016/day12.txt")), 0, 0);
+    run_both(&parse(include_str!("../input/2016/day23.txt")), 7, 0);
+    run_both(&parse(include_str!("../input/2016/day23.txt")), 8, 0);
+}
+
+#[test]
+fn test_optimize_jump_into_loop() {
+    // Landing mid-pattern runs the plain instructions until the loop comes round
+    run_both(&parse("cpy 3 b\njnz 1 3\ninc a\ndec b\njnz b -2"), 0, 0);
+}
+
+#[test]
+fn test_optimize_checked_overflow() {
+    // Overflowing part way round a loop stops where the plain ticks would
+    run_both(&parse("inc a\ndec c\njnz c -2"), i64::MAX - 1, 3);
+    let mul = "cpy 2 b\ncpy b d\ninc a\ndec d\njnz d -2\ndec c\njnz c -5";
+    run_both(&parse(mul), i64::MAX - 5, 4);
+}
+
+#[test]
+fn test_profile() {
+    let mut cpu = Cpu::new(parse(include_str!("../input/2016/day23.txt")));
+    cpu.set('a', 7).unwrap();
+    cpu.enable_profiling();
+    cpu.run();
+    let profile = cpu.profile().unwrap();
+    assert_eq!(profile.ticks(), profile.opcodes.values().sum());
+    assert_eq!(profile.toggled.iter().sum::<u64>(), 4);
+    let loops = profile.hot_loops();
+    assert_eq!((loops[0].start, loops[0].end), (2, 18));
+    let inner = loops.iter().find(|l| l.start == 5).unwrap();
+    assert_eq!((inner.end, inner.iterations), (7, 5033));
+    assert!(cpu.profile().unwrap().to_json().starts_with(r#"{"ticks":"#));
+
+    // Fused loops are still found, each run counting as one iteration
+    let mut cpu = Cpu::new(parse(include_str!("../input/2016/day23.txt")));
+    cpu.set('a', 7).unwrap();
+    cpu.optimize();
+    cpu.enable_profiling();
+    cpu.run();
+    let loops = cpu.profile().unwrap().hot_loops();
+    let mul = loops.iter().find(|l| l.start == 4).unwrap();
+    assert_eq!((mul.end, mul.iterations), (9, 5));
+}
+
+#[test]
+fn test_run_with_fuel() {
+    let mut cpu = Cpu::new(parse("cpy 3 a\nout a\ndec a\njnz a -2\njnz 1 -5"));
+    let mut fuel = 100;
+    assert_eq!(cpu.run_with_fuel(&mut fuel), Stop::Output(3));
+    assert_eq!(fuel, 98);
+    assert_eq!(cpu.run_with_fuel(&mut fuel), Stop::Output(2));
+    assert_eq!(cpu.run_with_fuel(&mut fuel), Stop::Output(1));
+    assert_eq!(
+        cpu.run_with_fuel(&mut fuel),
+        Stop::Fault(Fault::NegativePc(-1))
+    );
+
+    let mut cpu = Cpu::new(parse("jnz 1 0"));
+    assert_eq!(cpu.run_with_fuel(&mut 1000), Stop::OutOfFuel);
+
+    let mut cpu = Cpu::new(parse(include_str!("day12_example.txt")));
+    assert_eq!(cpu.run_with_fuel(&mut 1000), Stop::Halted);
+}
+
+#[test]
+fn test_registers() {
+    let dialect = Dialect {
+        registers: vec!['x', 'y'],
+        ..Default::default()
+    };
+    let program = Program::parse_with("cpy 3 x\ninc y\ndec x\njnz x -2", &dialect).unwrap();
+    let mut cpu = Cpu::with_registers(program, &['x', 'y']);
+    cpu.run();
+    assert_eq!(cpu.registers().collect_vec(), vec![('x', 0), ('y', 3)]);
+    assert_eq!(cpu.get('a'), Err(UnknownRegister('a')));
+    assert_eq!(cpu.set('a', 1), Err(UnknownRegister('a')));
+
+    let mut cpu = Cpu::with_registers(parse("inc a"), &['x']);
+    cpu.run();
+    assert_eq!(cpu.fault(), Some(Fault::UnknownRegister('a')));
+}
+
+#[test]
+fn test_arithmetic() {
+    let program = parse(include_str!("../input/2016/day23.txt"));
+    let factorial = |arithmetic, a| {
+        let mut cpu = Cpu::new(program.clone());
+        cpu.optimize();
+        cpu.set_arithmetic(arithmetic);
+        cpu.set('a', a).unwrap();
+        cpu.run();
+        (cpu.get('a').unwrap(), cpu.fault())
+    };
+    // 13! is past 32 bits, and 21! past 64
+    let big = 6_227_020_800 + 80 * 76;
+    assert_eq!(factorial(Arithmetic::I64, 13), (big, None));
+    assert_eq!(factorial(Arithmetic::Checked, 13), (big, None));
+    assert_eq!(
+        factorial(Arithmetic::Checked, 21).1,
+        Some(Fault::Overflow(5))
+    );
+
+    let mut cpu = Cpu::new(parse("cpy 2147483647 a\ninc a"));
+    cpu.run();
+    assert_eq!(cpu.get('a'), Ok(i32::MIN.into()));
+}
+
+#[test]
+fn test_receive() {
+    assert_eq!(
+        Program::parse("rcv a").unwrap_err().to_string(),
+        "line 1: unknown instruction `rcv`"
+    );
+    let dialect = Dialect {
+        receive: true,
+        ..Default::default()
+    };
+    let mut cpu = Cpu::new(Program::parse_with("rcv a\nrcv b", &dialect).unwrap());
+    assert_eq!(cpu.run_with_fuel(&mut 100), Stop::Blocked);
+    cpu.send(3);
+    cpu.send(4);
+    cpu.run();
+    assert_eq!(cpu.registers().collect_vec()[..2], [('a', 3), ('b', 4)]);
+    assert!(cpu.halted());
+}
+
+#[test]
+fn test_poke() {
+    let mut cpu = Cpu::new(parse("cpy 3 b\ninc a\ndec b\njnz b -2"));
+    cpu.optimize();
+    cpu.set_backend(Backend::Compiled);
+    cpu.enable_profiling();
+    assert!(cpu.poke(1, "inc c".parse().unwrap()));
+    assert!(cpu.poke(4, "out c".parse().unwrap()));
+    assert!(!cpu.poke(6, "out c".parse().unwrap()));
+    assert_eq!(cpu.run_till_output(), Some(3));
+    assert_eq!(cpu.get('a'), Ok(0));
+    assert_eq!(cpu.profile().unwrap().executed.len(), 5);
+
+    // Patching out a fault lets the program carry on
+    let mut cpu = Cpu::with_registers(parse("inc a"), &['x']);
+    assert_eq!(
+        cpu.run_with_fuel(&mut 10),
+        Stop::Fault(Fault::UnknownRegister('a'))
+    );
+    assert!(cpu.poke(0, Instruction::Inc(Value::Register('x'))));
+    assert_eq!(cpu.run_with_fuel(&mut 10), Stop::Halted);
+    assert_eq!(cpu.get('x'), Ok(1));
+}
+
+#[test]
+fn test_extended() {
+    assert_eq!(
+        Program::parse("mul a b").unwrap_err().to_string(),
+        "line 1: unknown instruction `mul`"
+    );
+    assert!(Program::parse("nop").is_err());
+
+    // 10! by hand, toggling its own `mul` into a `jnz` that skips an `out`
+    let source =
+        "cpy 10 b\ncpy 1 a\nmul b a\ndec b\njnz b -2\nnop\ncpy 2 c\ntgl c\nadd 5 a\nmul 2 2\nout a";
+    let program = Program::parse_with(source, &Dialect::extended()).unwrap();
+    assert_eq!(program.iter().join("\n"), source);
+    for backend in [Backend::Interpreter, Backend::Compiled] {
+        let mut cpu = Cpu::new(program.clone());
+        cpu.set_backend(backend);
+        cpu.set_arithmetic(Arithmetic::I64);
+        assert_eq!(cpu.run_till_output(), None);
+        assert_eq!(cpu.get('a'), Ok(3628800 + 5));
+        assert_eq!(cpu.program()[9], "jnz 2 2".parse().unwrap());
+    }
+}
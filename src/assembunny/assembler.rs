@@ -0,0 +1,248 @@
+use std::collections::{BTreeSet, HashMap};
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use super::{Dialect, Instruction, InstructionParseError, LineError, Value};
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AssembleError {
+    #[error("`{0}` is not a usable name")]
+    BadName(String),
+
+    #[error("`{0}` is already defined")]
+    DuplicateName(String),
+
+    #[error("bad constant `{0}`, expected `.const NAME VALUE`")]
+    BadConstant(String),
+
+    #[error("unknown label or constant `{0}`")]
+    UnknownName(String),
+
+    #[error("label `{0}` can only be the target of a `jnz`")]
+    MisplacedLabel(String),
+
+    #[error(transparent)]
+    Instruction(#[from] InstructionParseError),
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("{}", .0.iter().join("\n"))]
+pub struct AssemblyError(pub Vec<LineError<AssembleError>>);
+
+// Labels and constants share a namespace, which can't include the registers
+fn is_name(s: &str, dialect: &Dialect) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !dialect.registers.iter().any(|r| s == r.to_string())
+}
+
+// Source lines look like
+//
+//   ; comments run to the end of the line
+//   .const SIZE 282
+//   loop: inc d        ; a label can share a line with an instruction
+//         jnz b loop   ; and as an operand becomes the offset to it
+//
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, AssemblyError> {
+    assemble_with(source, &Dialect::default())
+}
+
+pub fn assemble_with(source: &str, dialect: &Dialect) -> Result<Vec<Instruction>, AssemblyError> {
+    Ok(assemble_lines(source, dialect)?
+        .into_iter()
+        .map(|(_, instruction)| instruction)
+        .collect())
+}
+
+// Each instruction along with the source line it came from
+pub(super) fn assemble_lines(
+    source: &str,
+    dialect: &Dialect,
+) -> Result<Vec<(usize, Instruction)>, AssemblyError> {
+    let mut errors = vec![];
+    let mut labels = HashMap::new();
+    let mut constants = HashMap::new();
+    let mut lines = vec![];
+
+    for (line, text) in source.lines().enumerate() {
+        let line = line + 1;
+        let mut text = text.split(';').next().unwrap_or_default().trim();
+        let mut error = None;
+
+        if let Some(rest) = text.strip_prefix(".const") {
+            match rest.split_ascii_whitespace().collect_vec()[..] {
+                [name, _] if !is_name(name, dialect) => {
+                    error = Some(AssembleError::BadName(name.into()))
+                }
+                [name, value] => match value.parse::<i32>() {
+                    Ok(_) if labels.contains_key(name) || constants.contains_key(name) => {
+                        error = Some(AssembleError::DuplicateName(name.into()))
+                    }
+                    Ok(value) => {
+                        constants.insert(name, value);
+                    }
+                    Err(_) => error = Some(AssembleError::BadConstant(rest.trim().into())),
+                },
+                _ => error = Some(AssembleError::BadConstant(rest.trim().into())),
+            }
+            text = "";
+        } else if let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if !is_name(label, dialect) {
+                error = Some(AssembleError::BadName(label.into()));
+            } else if labels.contains_key(label) || constants.contains_key(label) {
+                error = Some(AssembleError::DuplicateName(label.into()));
+            } else {
+                labels.insert(label, lines.len() as i32);
+            }
+            text = rest.trim();
+        }
+
+        if let Some(error) = error {
+            errors.push(LineError { line, error });
+        }
+        if !text.is_empty() {
+            lines.push((line, text));
+        }
+    }
+
+    let mut program = vec![];
+    for (pc, (line, text)) in lines.into_iter().enumerate() {
+        let mut toks = text.split_ascii_whitespace();
+        let op = toks.next().unwrap_or_default();
+        let resolved = toks
+            .enumerate()
+            .map(|(i, tok)| {
+                if let Some(addr) = labels.get(tok) {
+                    // Only a jump's offset is relative to the instruction
+                    if op == "jnz" && i == 1 {
+                        Ok((addr - pc as i32).to_string())
+                    } else {
+                        Err(AssembleError::MisplacedLabel(tok.into()))
+                    }
+                } else if let Some(value) = constants.get(tok) {
+                    Ok(value.to_string())
+                } else if is_name(tok, dialect) && tok.len() > 1 {
+                    Err(AssembleError::UnknownName(tok.into()))
+                } else {
+                    Ok(tok.to_string())
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|operands| {
+                let text = format!("{op} {}", operands.join(" "));
+                Ok(Instruction::parse_with(&text, dialect)?)
+            });
+        match resolved {
+            Ok(instruction) => program.push((line, instruction)),
+            Err(error) => errors.push(LineError { line, error }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(program)
+    } else {
+        errors.sort_by_key(|e| e.line);
+        Err(AssemblyError(errors))
+    }
+}
+
+// Print a program as assembler source, labelling the target of every jump by
+// a literal offset.  Assembling the result gives back the same program.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let target = |pc: usize| match program[pc] {
+        Instruction::Jnz(_, Value::Literal(offset)) => (pc as i32)
+            .checked_add(offset)
+            .filter(|addr| (0..=program.len() as i32).contains(addr))
+            .map(|addr| addr as usize),
+        _ => None,
+    };
+    let targets: BTreeSet<usize> = (0..program.len()).filter_map(target).collect();
+
+    let mut out = String::new();
+    for (pc, instruction) in program.iter().enumerate() {
+        let label = if targets.contains(&pc) {
+            format!("L{pc}:")
+        } else {
+            String::new()
+        };
+        let text = match (instruction, target(pc)) {
+            (Instruction::Jnz(test, _), Some(addr)) => format!("jnz {test} L{addr}"),
+            _ => instruction.to_string(),
+        };
+        out += &format!("{label:<6}{text}\n");
+    }
+    if targets.contains(&program.len()) {
+        out += &format!("L{}:\n", program.len());
+    }
+    out
+}
+
+#[test]
+fn test_assemble_labelled_source() {
+    assert_eq!(
+        assemble(include_str!("../day23_source.txt")).unwrap(),
+        super::parse(include_str!("../../input/2016/day23.txt"))
+    );
+}
+
+#[test]
+fn test_disassemble_round_trip() {
+    for input in [
+        include_str!("../../input/2016/day12.txt"),
+        include_str!("../../input/2016/day23.txt"),
+        include_str!("../../input/2016/day25.txt"),
+    ] {
+        let program = super::parse(input);
+        assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+        let listing = program.iter().join("\n");
+        assert_eq!(super::Program::parse(&listing).unwrap(), program);
+    }
+
+    // Toggles leave behind things like `cpy 1 c`, which must survive too
+    let mut cpu = super::day23();
+    cpu.run();
+    let toggled = cpu.program();
+    assert_ne!(toggled, super::day23().program());
+    assert_eq!(assemble(&disassemble(toggled)).unwrap(), toggled);
+
+    // A jump too far to label is left as it is
+    let program = assemble("inc a\njnz 1 2147483647").unwrap();
+    assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+}
+
+#[test]
+fn test_assemble_errors() {
+    let err = assemble(".const a 1\n.const N x\nstart: inc a\nstart: jnz a nowhere\nfoo bar")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 1: `a` is not a usable name\n\
+         line 2: bad constant `N x`, expected `.const NAME VALUE`\n\
+         line 4: `start` is already defined\n\
+         line 4: unknown label or constant `nowhere`\n\
+         line 5: unknown label or constant `bar`"
+    );
+
+    let err = assemble("loop: inc loop\njnz loop 0\ntgl loop").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 1: label `loop` can only be the target of a `jnz`\n\
+         line 2: label `loop` can only be the target of a `jnz`\n\
+         line 3: label `loop` can only be the target of a `jnz`"
+    );
+
+    // Names are kept apart from whichever registers the dialect has
+    let dialect = super::Dialect {
+        registers: vec!['x', 'y'],
+        ..super::Dialect::default()
+    };
+    let err = assemble_with("x: inc y\njnz 1 x", &dialect).unwrap_err();
+    assert_eq!(err.to_string(), "line 1: `x` is not a usable name");
+    let program = assemble_with("a: inc x\njnz y a", &dialect).unwrap();
+    assert_eq!(program[1].to_string(), "jnz y -1");
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use super::{Dialect, Instruction, Program, Register, Value};
+
+// day25's output loop, with `w` holding the number and x, y, z working
+// registers.  Entered at the top with a copy of the number in w, it prints
+// the number's binary digits, lowest first, then starts again.
+const EMITTER: &str = "\
+cpy w x
+jnz 0 0
+cpy x y
+cpy 0 x
+cpy 2 z
+jnz y 2
+jnz 1 6
+dec y
+dec z
+jnz z -4
+inc x
+jnz 1 -7
+cpy 2 y
+jnz z 2
+jnz 1 4
+dec y
+dec z
+jnz 1 -4
+jnz 0 0
+out y
+jnz x -19
+jnz 1 -21";
+
+// The first test of the number being divided, where `a` starts to matter
+const EMITTER_BRANCH: usize = 5;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ClockError {
+    #[error("the program halts, so isn't a clock")]
+    Halted,
+
+    #[error("`{instruction}` at {pc} {reason}")]
+    Unsupported {
+        pc: usize,
+        instruction: Instruction,
+        reason: Unfollowable,
+    },
+
+    #[error("gave up after {0} ticks without `a` deciding a branch")]
+    OutOfFuel(u64),
+
+    #[error("the code around {0} isn't the usual loop printing the bits of a number")]
+    NotBitEmitter(usize),
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unfollowable {
+    #[error("uses `a` in a way this can't follow")]
+    Symbolic,
+
+    #[error("uses a register other than `a` to `d`")]
+    Register,
+
+    #[error("would overflow, where the Cpu wraps")]
+    Overflow,
+
+    #[error("prints before `a` decides a branch")]
+    EarlyOutput,
+
+    #[error("waits for input")]
+    Input,
+}
+
+// The output of a clock program started with a given `a`: the binary digits
+// of `scale * a + offset`, lowest first, over and over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Clock {
+    pub scale: i64,
+    pub offset: i64,
+}
+
+impl Clock {
+    pub fn value(&self, a: i64) -> i64 {
+        self.scale * a + self.offset
+    }
+
+    // One period of the output, or None if the program never prints anything
+    pub fn period(&self, a: i64) -> Option<Vec<i64>> {
+        let mut n = self.value(a);
+        match n {
+            ..=-1 => None,
+            0 => Some(vec![0]),
+            _ => {
+                let mut bits = vec![];
+                while n > 0 {
+                    bits.push(n % 2);
+                    n /= 2;
+                }
+                Some(bits)
+            }
+        }
+    }
+
+    // Printing 0, 1, 0, 1... forever, even across the repeat, takes a number
+    // whose binary digits are 1010...10.
+    pub fn smallest_clock(&self) -> Option<i64> {
+        let mut target = 2i64;
+        let mut best: Option<i64> = None;
+        loop {
+            let diff = target - self.offset;
+            if diff.checked_rem(self.scale) == Some(0) && diff / self.scale >= 0 {
+                let a = diff / self.scale;
+                best = Some(best.map_or(a, |b| b.min(a)));
+            }
+            match target.checked_mul(4).and_then(|t| t.checked_add(2)) {
+                Some(next) => target = next,
+                None => return best,
+            }
+        }
+    }
+}
+
+// Each register as `k * a + m`, for the `a` the program starts with
+type Affine = (i64, i64);
+
+// Run the program with `a` left unknown until it first branches on it.  That
+// should be the bit printing loop, working on some `k * a + m`.  Both stay in
+// i32 range, and the analysis gives up rather than follow the Cpu wrapping.
+pub fn analyze_clock(program: &[Instruction], mut fuel: u64) -> Result<Clock, ClockError> {
+    let budget = fuel;
+    let mut program = program.to_vec();
+    let mut regs: HashMap<Register, Affine> =
+        HashMap::from([('a', (1, 0)), ('b', (0, 0)), ('c', (0, 0)), ('d', (0, 0))]);
+    let mut pc = 0;
+
+    loop {
+        if pc < 0 || pc >= program.len() as i64 {
+            return Err(ClockError::Halted);
+        }
+        if fuel == 0 {
+            return Err(ClockError::OutOfFuel(budget));
+        }
+        fuel -= 1;
+
+        let here = pc as usize;
+        let instruction = program[here];
+        let unsupported = |reason| ClockError::Unsupported {
+            pc: here,
+            instruction,
+            reason,
+        };
+        let eval = |v: Value| match v {
+            Value::Literal(n) => Ok((0, n.into())),
+            Value::Register(r) => regs
+                .get(&r)
+                .copied()
+                .ok_or(unsupported(Unfollowable::Register)),
+        };
+        let concrete = |v: Value| match eval(v)? {
+            (0, m) => Ok(m),
+            _ => Err(unsupported(Unfollowable::Symbolic)),
+        };
+        // Operands are in i32 range, so working in i64 can't overflow first
+        let fit = |(k, m): Affine| match (i32::try_from(k), i32::try_from(m)) {
+            (Ok(_), Ok(_)) => Ok((k, m)),
+            _ => Err(unsupported(Unfollowable::Overflow)),
+        };
+        let mut next = 1;
+        match instruction {
+            Instruction::Cpy(src, Value::Register(r)) => {
+                let (value, _) = (eval(src)?, eval(Value::Register(r))?);
+                regs.insert(r, value);
+            }
+            Instruction::Inc(Value::Register(r)) => {
+                let (k, m) = eval(Value::Register(r))?;
+                regs.insert(r, fit((k, m + 1))?);
+            }
+            Instruction::Dec(Value::Register(r)) => {
+                let (k, m) = eval(Value::Register(r))?;
+                regs.insert(r, fit((k, m - 1))?);
+            }
+            Instruction::Add(src, Value::Register(r)) => {
+                let ((k, m), (j, n)) = (eval(src)?, eval(Value::Register(r))?);
+                regs.insert(r, fit((k + j, m + n))?);
+            }
+            Instruction::Mul(src, Value::Register(r)) => {
+                // Still affine as long as one side doesn't depend on `a`
+                let product = match (eval(src)?, eval(Value::Register(r))?) {
+                    ((0, m), (j, n)) | ((j, n), (0, m)) => (j * m, n * m),
+                    _ => return Err(unsupported(Unfollowable::Symbolic)),
+                };
+                regs.insert(r, fit(product)?);
+            }
+            Instruction::Jnz(test, jump) => match concrete(test) {
+                Ok(0) => (),
+                Ok(_) => next = concrete(jump)?,
+                Err(ClockError::Unsupported {
+                    reason: Unfollowable::Symbolic,
+                    ..
+                }) => return emitter(&program, here, &regs),
+                Err(e) => return Err(e),
+            },
+            Instruction::Tgl(offset) => {
+                let addr = pc + concrete(offset)?;
+                if let Some(target) = program.get_mut(addr as usize).filter(|_| addr >= 0) {
+                    *target = target.toggled();
+                }
+            }
+            Instruction::Out(_) => return Err(unsupported(Unfollowable::EarlyOutput)),
+            Instruction::Rcv(_) => return Err(unsupported(Unfollowable::Input)),
+            _ => (),
+        }
+        pc += next;
+    }
+}
+
+fn emitter(
+    program: &[Instruction],
+    branch: usize,
+    regs: &HashMap<Register, Affine>,
+) -> Result<Clock, ClockError> {
+    let not_emitter = ClockError::NotBitEmitter(branch);
+    let dialect = Dialect {
+        registers: vec!['w', 'x', 'y', 'z'],
+        ..Default::default()
+    };
+    let template = Program::parse_with(EMITTER, &dialect).unwrap();
+    let start = branch
+        .checked_sub(EMITTER_BRANCH)
+        .ok_or(ClockError::NotBitEmitter(branch))?;
+    let code = program
+        .get(start..start + template.len())
+        .ok_or(not_emitter)?;
+
+    // The template's registers must map one-to-one onto the program's
+    let mut roles = HashMap::new();
+    let mut bind = |t: Value, p: Value| match (t, p) {
+        (Value::Literal(m), Value::Literal(n)) => m == n,
+        (Value::Register(role), Value::Register(r)) => *roles.entry(role).or_insert(r) == r,
+        _ => false,
+    };
+    let matches = template.iter().zip(code).all(|(t, p)| match (*t, *p) {
+        (Instruction::Cpy(a, b), Instruction::Cpy(c, d))
+        | (Instruction::Jnz(a, b), Instruction::Jnz(c, d)) => bind(a, c) && bind(b, d),
+        (Instruction::Inc(a), Instruction::Inc(b))
+        | (Instruction::Dec(a), Instruction::Dec(b))
+        | (Instruction::Out(a), Instruction::Out(b)) => bind(a, b),
+        _ => false,
+    });
+    if !matches || !roles.values().all_unique() {
+        return Err(ClockError::NotBitEmitter(branch));
+    }
+
+    // Arriving at the branch the number is in y, with w still holding it for
+    // the next time round
+    let reg = |role| regs.get(&roles[&role]).copied();
+    let number = reg('y');
+    if number != reg('w') || reg('x') != Some((0, 0)) || reg('z') != Some((0, 2)) {
+        return Err(ClockError::NotBitEmitter(branch));
+    }
+    let Some((scale, offset)) = number else {
+        return Err(ClockError::NotBitEmitter(branch));
+    };
+    Ok(Clock { scale, offset })
+}
+
+#[test]
+fn test_analyze_day25() {
+    let program = super::parse(include_str!("../../input/2016/day25.txt"));
+    let clock = analyze_clock(&program, 1_000_000).unwrap();
+    assert_eq!(
+        clock,
+        Clock {
+            scale: 1,
+            offset: 282 * 9
+        }
+    );
+    assert_eq!(clock.smallest_clock(), Some(192));
+
+    // Agrees with actually running it
+    for a in [0, 5, 192, 1000] {
+        let mut cpu = super::Cpu::new(program.clone());
+        cpu.set('a', a).unwrap();
+        let cycle = cpu.find_cycle(1_000_000).unwrap();
+        let period = clock.period(a).unwrap();
+        let n = 3 * period.len();
+        assert_eq!(cycle.repeat.len(), period.len());
+        assert!(cycle
+            .iter()
+            .take(n)
+            .eq(period.iter().copied().cycle().take(n)));
+    }
+}
+
+#[test]
+fn test_analyze_unsupported() {
+    let day12 = super::parse(include_str!("../../input/2016/day12.txt"));
+    assert_eq!(analyze_clock(&day12, 1_000_000), Err(ClockError::Halted));
+    assert_eq!(
+        analyze_clock(&super::parse("out a"), 100)
+            .unwrap_err()
+            .to_string(),
+        "`out a` at 0 prints before `a` decides a branch"
+    );
+    let extended = Program::parse_with("add 2147483647 b\ninc b", &Dialect::extended()).unwrap();
+    assert_eq!(
+        analyze_clock(&extended, 100).unwrap_err().to_string(),
+        "`inc b` at 1 would overflow, where the Cpu wraps"
+    );
+    let dialect = Dialect {
+        registers: vec!['a', 'e'],
+        ..Dialect::default()
+    };
+    assert!(matches!(
+        analyze_clock(&Program::parse_with("inc e", &dialect).unwrap(), 100),
+        Err(ClockError::Unsupported {
+            reason: Unfollowable::Register,
+            ..
+        })
+    ));
+    assert_eq!(
+        analyze_clock(&super::parse("jnz 1 0"), 100),
+        Err(ClockError::OutOfFuel(100))
+    );
+    let extended = Program::parse_with("mul a a\njnz a 2", &Dialect::extended()).unwrap();
+    assert!(matches!(
+        analyze_clock(&extended, 100),
+        Err(ClockError::Unsupported { pc: 0, .. })
+    ));
+    assert_eq!(
+        analyze_clock(&super::parse("jnz a 2\nout 1\nout 0"), 100),
+        Err(ClockError::NotBitEmitter(0))
+    );
+}
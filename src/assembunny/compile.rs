@@ -0,0 +1,190 @@
+use super::{Cpu, Fault, Instruction, Register, UnknownRegister, Value};
+
+// Which engine `tick` runs instructions on.  Both give the same results; the
+// compiled one resolves registers to slots up front instead of on every tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Interpreter,
+    Compiled,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Operand {
+    Const(i64),
+    Slot(usize),
+    // Not in this Cpu's register file, which faults only if it's reached
+    Unknown(Register),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Op {
+    Nop,
+    Copy(Operand, Operand),
+    Add(Operand, i128),
+    Sum(Operand, Operand),
+    Product(Operand, Operand),
+    Jnz(Operand, Operand),
+    Tgl(Operand),
+    Out(Operand),
+    Rcv(Operand),
+}
+
+impl Cpu {
+    fn operand(&self, value: Value) -> Operand {
+        match value {
+            Value::Literal(n) => Operand::Const(n.into()),
+            Value::Register(r) => self.slot(r).map_or(Operand::Unknown(r), Operand::Slot),
+        }
+    }
+
+    // Instructions aimed at a literal do nothing, so compile to nothing
+    fn compile(&self, instruction: Instruction) -> Op {
+        let register = |v: Value| matches!(v, Value::Register(_));
+        match instruction {
+            Instruction::Cpy(src, dest) if register(dest) => {
+                Op::Copy(self.operand(src), self.operand(dest))
+            }
+            Instruction::Inc(dest) if register(dest) => Op::Add(self.operand(dest), 1),
+            Instruction::Dec(dest) if register(dest) => Op::Add(self.operand(dest), -1),
+            Instruction::Jnz(test, jump) => Op::Jnz(self.operand(test), self.operand(jump)),
+            Instruction::Tgl(offset) if register(offset) => Op::Tgl(self.operand(offset)),
+            Instruction::Out(src) => Op::Out(self.operand(src)),
+            Instruction::Rcv(dest) if register(dest) => Op::Rcv(self.operand(dest)),
+            Instruction::Add(src, dest) if register(dest) => {
+                Op::Sum(self.operand(src), self.operand(dest))
+            }
+            Instruction::Mul(src, dest) if register(dest) => {
+                Op::Product(self.operand(src), self.operand(dest))
+            }
+            _ => Op::Nop,
+        }
+    }
+
+    pub fn set_backend(&mut self, backend: Backend) {
+        self.ops = match backend {
+            Backend::Interpreter => None,
+            Backend::Compiled => Some(self.program.iter().map(|&i| self.compile(i)).collect()),
+        };
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self.ops {
+            Some(_) => Backend::Compiled,
+            None => Backend::Interpreter,
+        }
+    }
+
+    // After a `tgl`, only the instruction it rewrote needs compiling again
+    pub(super) fn recompile(&mut self, addr: usize) {
+        let op = self.compile(self.program[addr]);
+        if let Some(ops) = &mut self.ops {
+            ops[addr] = op;
+        }
+    }
+
+    fn load(&self, operand: Operand) -> Result<i64, Fault> {
+        match operand {
+            Operand::Const(n) => Ok(n),
+            Operand::Slot(slot) => Ok(self.registers[slot]),
+            Operand::Unknown(r) => Err(UnknownRegister(r).into()),
+        }
+    }
+
+    fn store(&mut self, dest: Operand, value: i128) -> Result<(), Fault> {
+        let value = self.arithmetic.fit(value).ok_or(Fault::Overflow(self.pc))?;
+        match dest {
+            Operand::Slot(slot) => self.assign(slot, value),
+            Operand::Unknown(r) => return Err(UnknownRegister(r).into()),
+            Operand::Const(_) => (),
+        }
+        Ok(())
+    }
+
+    pub(super) fn exec(&mut self, pc: i32, op: Op) -> Result<(), Fault> {
+        let mut next = 1;
+        match op {
+            Op::Nop => (),
+            Op::Copy(src, dest) => self.store(dest, self.load(src)?.into())?,
+            Op::Add(dest, n) => self.store(dest, self.load(dest)? as i128 + n)?,
+            Op::Sum(src, dest) => {
+                self.store(dest, self.load(dest)? as i128 + self.load(src)? as i128)?
+            }
+            Op::Product(src, dest) => {
+                self.store(dest, self.load(dest)? as i128 * self.load(src)? as i128)?
+            }
+            Op::Jnz(test, jump) => {
+                let taken = self.load(test)? != 0;
+                if taken {
+                    next = self.load(jump)?.clamp(i32::MIN.into(), i32::MAX.into()) as i32;
+                }
+                if let Some(profile) = &mut self.profile {
+                    profile.jump(pc, taken.then_some(pc.saturating_add(next)));
+                }
+            }
+            Op::Tgl(offset) => self.toggle((pc as i64).saturating_add(self.load(offset)?)),
+            Op::Out(src) => self.output = Some(self.load(src)?),
+            Op::Rcv(dest) => match self.receive() {
+                Some(v) => self.store(dest, v.into())?,
+                None => next = 0,
+            },
+        }
+        self.pc = pc.saturating_add(next);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn both(input: &str, setup: impl Fn(&mut Cpu), fuel: u64) {
+    let program = super::parse(input);
+    let [plain, compiled] = [Backend::Interpreter, Backend::Compiled].map(|backend| {
+        let mut cpu = Cpu::new(program.clone());
+        cpu.set_backend(backend);
+        setup(&mut cpu);
+        let mut fuel = fuel;
+        let mut outputs = vec![];
+        while let super::Stop::Output(v) = cpu.run_with_fuel(&mut fuel) {
+            outputs.push(v);
+        }
+        assert_eq!(cpu.backend(), backend);
+        (cpu.snapshot(), outputs, cpu.fault())
+    });
+    assert_eq!(plain, compiled);
+}
+
+#[test]
+fn test_compiled_matches_interpreter() {
+    let day12 = include_str!("../../input/2016/day12.txt");
+    let day23 = include_str!("../../input/2016/day23.txt");
+    let day25 = include_str!("../../input/2016/day25.txt");
+    both(day12, |_| (), 50_000_000);
+    both(
+        day12,
+        |cpu| {
+            cpu.optimize();
+            cpu.set('c', 1).unwrap()
+        },
+        50_000_000,
+    );
+    both(day23, |cpu| cpu.set('a', 7).unwrap(), 50_000_000);
+    both(
+        day23,
+        |cpu| {
+            cpu.optimize();
+            cpu.set('a', 12).unwrap()
+        },
+        50_000_000,
+    );
+    both(day25, |cpu| cpu.set('a', 192).unwrap(), 1_000_000);
+    both(day25, |cpu| cpu.set('a', 5).unwrap(), 1_000_000);
+}
+
+#[test]
+fn test_compiled_faults() {
+    both("inc a\njnz a -3", |_| (), 100);
+    let mut cpu = Cpu::with_registers(super::Program::parse("cpy 2 b\ninc a").unwrap(), &['b']);
+    cpu.set_backend(Backend::Compiled);
+    cpu.run();
+    assert_eq!(cpu.fault(), Some(Fault::UnknownRegister('a')));
+    assert_eq!(cpu.get('b'), Ok(2));
+}
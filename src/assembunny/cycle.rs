@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use super::{Cpu, Instruction, Stop};
+
+// An output signal that has been shown to repeat forever: `prefix` once,
+// then `repeat` over and over.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: Vec<i64>,
+    pub repeat: Vec<i64>,
+}
+
+impl Cycle {
+    pub fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        self.prefix
+            .iter()
+            .chain(self.repeat.iter().cycle())
+            .copied()
+    }
+}
+
+impl Cpu {
+    fn state(&self) -> (Vec<i64>, i32, Vec<Instruction>) {
+        (self.registers.clone(), self.pc, self.program.clone())
+    }
+
+    // Run until the machine is back in a state it was in straight after an
+    // earlier `out`.  Everything from then on is a replay, so the outputs in
+    // between repeat forever.  Gives up if the program stops, or hasn't
+    // repeated within `fuel` ticks.
+    pub fn find_cycle(&mut self, mut fuel: u64) -> Option<Cycle> {
+        let mut seen = HashMap::new();
+        let mut outputs = vec![];
+        while let Stop::Output(v) = self.run_with_fuel(&mut fuel) {
+            outputs.push(v);
+            if let Some(start) = seen.insert(self.state(), outputs.len()) {
+                let repeat = outputs.split_off(start);
+                return Some(Cycle {
+                    prefix: outputs,
+                    repeat,
+                });
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn test_find_cycle() {
+    let mut cpu = Cpu::new(super::parse(
+        "out 5\ncpy 2 a\nout a\ndec a\njnz a -2\njnz 1 -4",
+    ));
+    let cycle = cpu.find_cycle(1000).unwrap();
+    assert_eq!(cycle.prefix, vec![5, 2]);
+    assert_eq!(cycle.repeat, vec![1, 2]);
+    assert_eq!(
+        cycle.iter().take(6).collect::<Vec<_>>(),
+        vec![5, 2, 1, 2, 1, 2]
+    );
+}
+
+#[test]
+fn test_find_cycle_gives_up() {
+    assert_eq!(
+        Cpu::new(super::parse("out 1\nout 2")).find_cycle(1000),
+        None
+    );
+    assert_eq!(
+        Cpu::new(super::parse("inc a\nout a\njnz 1 -2")).find_cycle(1000),
+        None
+    );
+}
@@ -0,0 +1,249 @@
+use std::collections::BTreeSet;
+
+use super::{Cpu, Fault, Instruction, Register, UnknownRegister, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Toggled {
+        addr: usize,
+        from: Instruction,
+        to: Instruction,
+    },
+    Watch {
+        register: Register,
+        from: i64,
+        to: i64,
+    },
+    Output(i64),
+    Breakpoint(i32),
+    // See `Cpu::waiting`
+    Waiting(i32),
+    Halted,
+    Fault(Fault),
+}
+
+impl Event {
+    // Toggles and outputs are worth a note, but don't stop a `cont`
+    pub fn stops(&self) -> bool {
+        !matches!(self, Event::Toggled { .. } | Event::Output(_))
+    }
+}
+
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Event::Toggled { addr, from, to } => write!(f, "tgl {addr}: {from} -> {to}"),
+            Event::Watch { register, from, to } => write!(f, "watch {register}: {from} -> {to}"),
+            Event::Output(v) => write!(f, "out {v}"),
+            Event::Breakpoint(pc) => write!(f, "break at {pc}"),
+            Event::Waiting(pc) => write!(f, "waiting for input at {pc}"),
+            Event::Halted => write!(f, "halted"),
+            Event::Fault(fault) => write!(f, "fault: {fault:?}"),
+        }
+    }
+}
+
+pub struct Debugger {
+    cpu: Cpu,
+    breakpoints: BTreeSet<i32>,
+    watchpoints: BTreeSet<Register>,
+}
+
+impl Debugger {
+    pub fn new(cpu: Cpu) -> Self {
+        Self {
+            cpu,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+        }
+    }
+
+    pub fn cpu(&self) -> &Cpu {
+        &self.cpu
+    }
+
+    pub fn cpu_mut(&mut self) -> &mut Cpu {
+        &mut self.cpu
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &i32> {
+        self.breakpoints.iter()
+    }
+
+    pub fn watchpoints(&self) -> impl Iterator<Item = &Register> {
+        self.watchpoints.iter()
+    }
+
+    pub fn set_breakpoint(&mut self, pc: i32) {
+        self.breakpoints.insert(pc);
+    }
+
+    pub fn clear_breakpoint(&mut self, pc: i32) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    pub fn watch(&mut self, register: Register) -> Result<(), UnknownRegister> {
+        self.cpu.get(register)?;
+        self.watchpoints.insert(register);
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self, register: Register) -> bool {
+        self.watchpoints.remove(&register)
+    }
+
+    // Execute a single tick (or a whole fused loop on an optimized Cpu),
+    // reporting everything interesting that happened.
+    pub fn step(&mut self) -> Vec<Event> {
+        if self.cpu.halted() {
+            return vec![self.stopped()];
+        }
+        if self.cpu.waiting() {
+            return vec![Event::Waiting(self.cpu.pc)];
+        }
+        let cpu = &mut self.cpu;
+        let toggle = match cpu.program.get(cpu.pc as usize) {
+            Some(Instruction::Tgl(Value::Register(r))) => cpu.get(*r).ok().and_then(|offset| {
+                let addr = cpu.pc as i64 + offset;
+                cpu.program
+                    .get(addr as usize)
+                    .filter(|_| addr >= 0)
+                    .map(|&from| (addr as usize, from))
+            }),
+            _ => None,
+        };
+        let watched = self
+            .watchpoints
+            .iter()
+            .filter_map(|&r| Some((r, cpu.get(r).ok()?)))
+            .collect::<Vec<_>>();
+
+        cpu.output = None;
+        cpu.tick();
+
+        let mut events = vec![];
+        if let Some((addr, from)) = toggle {
+            events.push(Event::Toggled {
+                addr,
+                from,
+                to: cpu.program[addr],
+            });
+        }
+        for (register, from) in watched {
+            let to = cpu.get(register).unwrap_or(from);
+            if from != to {
+                events.push(Event::Watch { register, from, to });
+            }
+        }
+        if let Some(v) = cpu.output {
+            events.push(Event::Output(v));
+        }
+        if cpu.halted() {
+            events.push(self.stopped());
+        } else if self.breakpoints.contains(&cpu.pc) {
+            events.push(Event::Breakpoint(cpu.pc));
+        }
+        events
+    }
+
+    fn stopped(&self) -> Event {
+        self.cpu.fault().map_or(Event::Halted, Event::Fault)
+    }
+
+    // Run until a breakpoint, watchpoint or halt
+    pub fn cont(&mut self) -> Vec<Event> {
+        self.run_until(Event::stops)
+    }
+
+    // Run until the next `out`, stopping early at a breakpoint or watchpoint
+    pub fn next_output(&mut self) -> Vec<Event> {
+        self.run_until(|e| e.stops() || matches!(e, Event::Output(_)))
+    }
+
+    fn run_until(&mut self, stop: impl Fn(&Event) -> bool) -> Vec<Event> {
+        let mut events = vec![];
+        loop {
+            let step = self.step();
+            let done = step.iter().any(&stop);
+            events.extend(step);
+            if done {
+                return events;
+            }
+        }
+    }
+
+    // Disassembly of the instructions within `radius` of pc
+    pub fn listing(&self, radius: usize) -> Vec<String> {
+        let pc = self.cpu.pc;
+        let radius = radius.try_into().unwrap_or(i32::MAX);
+        let from = pc.saturating_sub(radius).max(0);
+        let to = pc
+            .saturating_add(radius)
+            .min(self.cpu.program.len() as i32 - 1);
+        (from..=to)
+            .map(|addr| {
+                format!(
+                    "{}{}{addr:>4}  {}",
+                    if addr == pc { "=>" } else { "  " },
+                    if self.breakpoints.contains(&addr) {
+                        '*'
+                    } else {
+                        ' '
+                    },
+                    self.cpu.program[addr as usize]
+                )
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_debugger_breakpoint() {
+    let mut dbg = Debugger::new(Cpu::new(super::parse(include_str!("../day12_example.txt"))));
+    dbg.set_breakpoint(4);
+    assert_eq!(dbg.cont(), vec![Event::Breakpoint(4)]);
+    assert_eq!(dbg.cpu().get('a'), Ok(42));
+    assert_eq!(dbg.cont(), vec![Event::Halted]);
+}
+
+#[test]
+fn test_debugger_watch_and_toggle() {
+    let mut dbg = Debugger::new(Cpu::new(super::parse(
+        "cpy 2 a\ntgl a\ninc a\ninc a\nout a\ninc b",
+    )));
+    dbg.watch('a').unwrap();
+    assert_eq!(
+        dbg.cont(),
+        vec![Event::Watch {
+            register: 'a',
+            from: 0,
+            to: 2
+        }]
+    );
+    assert_eq!(
+        dbg.step(),
+        vec![Event::Toggled {
+            addr: 3,
+            from: Instruction::Inc(Value::Register('a')),
+            to: Instruction::Dec(Value::Register('a')),
+        }]
+    );
+    dbg.unwatch('a');
+    assert_eq!(dbg.next_output(), vec![Event::Output(2)]);
+    assert_eq!(dbg.listing(1), vec!["      4  out a", "=>    5  inc b"]);
+}
+
+#[test]
+fn test_debugger_waiting() {
+    let mut dbg = Debugger::new(Cpu::new(super::parse("rcv a\nout a\njnz 1 -2")));
+    assert_eq!(dbg.cont(), vec![Event::Waiting(0)]);
+    assert_eq!(dbg.next_output(), vec![Event::Waiting(0)]);
+    dbg.cpu_mut().send(7);
+    assert_eq!(dbg.next_output(), vec![Event::Output(7)]);
+    assert_eq!(dbg.cont(), vec![Event::Waiting(0)]);
+
+    // Far out of range, after a fault
+    let mut dbg = Debugger::new(Cpu::new(super::parse("jnz 1 -2147483648")));
+    assert_eq!(dbg.cont(), vec![Event::Fault(Fault::NegativePc(i32::MIN))]);
+    assert_eq!(dbg.listing(3), Vec::<String>::new());
+}
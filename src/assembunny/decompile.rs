@@ -0,0 +1,694 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use itertools::Itertools;
+
+use super::optimize::{optimize, Fused};
+use super::{Instruction, Register, Value};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Lit(i32),
+    Reg(Register),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Factorial(Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Until {
+    // Round again while the register is non-zero
+    Zero(Register),
+    Never,
+    // The body can rewrite the jump back to the top
+    Toggled(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stmt {
+    Assign(Register, Expr),
+    // Assignments that all read the values from before any of them
+    Parallel(Vec<(Register, Expr)>),
+    Out(Expr),
+    Receive(Register),
+    // Toggle the instruction at this address
+    Toggle(Expr),
+    Loop {
+        body: Vec<Stmt>,
+        until: Until,
+    },
+    If {
+        zero: Register,
+        body: Vec<Stmt>,
+    },
+    Label(usize),
+    Goto {
+        unless_zero: Option<Register>,
+        addr: usize,
+    },
+    // A jump by a register offset from `pc`
+    JumpBy {
+        unless_zero: Option<Register>,
+        pc: usize,
+        offset: Register,
+    },
+    // What the following code takes for granted
+    Assume(Expr, i32),
+}
+
+fn lit(v: Value) -> Expr {
+    match v {
+        Value::Literal(n) => Expr::Lit(n),
+        Value::Register(r) => Expr::Reg(r),
+    }
+}
+
+fn add(a: Expr, b: Expr) -> Expr {
+    Expr::Add(Box::new(a), Box::new(b))
+}
+
+fn mul(a: Expr, b: Expr) -> Expr {
+    Expr::Mul(Box::new(a), Box::new(b))
+}
+
+impl Expr {
+    fn reads(&self, r: Register) -> bool {
+        match self {
+            Expr::Lit(_) => false,
+            Expr::Reg(s) => *s == r,
+            Expr::Add(a, b) | Expr::Mul(a, b) => a.reads(r) || b.reads(r),
+            Expr::Factorial(a) => a.reads(r),
+        }
+    }
+
+    fn subst(&self, env: &BTreeMap<Register, Expr>) -> Expr {
+        match self {
+            Expr::Lit(_) => self.clone(),
+            Expr::Reg(r) => env.get(r).cloned().unwrap_or(Expr::Reg(*r)),
+            Expr::Add(a, b) => add(a.subst(env), b.subst(env)),
+            Expr::Mul(a, b) => mul(a.subst(env), b.subst(env)),
+            Expr::Factorial(a) => Expr::Factorial(Box::new(a.subst(env))),
+        }
+    }
+
+    // `k * r + m`, if that's all the expression is
+    fn linear(&self, r: Register) -> Option<(i64, i64)> {
+        match self {
+            Expr::Lit(n) => Some((0, *n as i64)),
+            Expr::Reg(s) if *s == r => Some((1, 0)),
+            Expr::Add(a, b) => {
+                let ((ka, ma), (kb, mb)) = (a.linear(r)?, b.linear(r)?);
+                Some((ka + kb, ma + mb))
+            }
+            Expr::Mul(a, b) => match (a.linear(r)?, b.linear(r)?) {
+                ((0, n), (k, m)) | ((k, m), (0, n)) => Some((k * n, m * n)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // Tidy up sums, and spot `x * (x - 1)!`.  Products of literals are left
+    // alone, as they're usually the interesting constants of a puzzle.
+    fn simplify(self) -> Expr {
+        use Expr::*;
+        match self {
+            Add(a, b) => match (a.simplify(), b.simplify()) {
+                (Lit(m), Lit(n)) => Lit(m.wrapping_add(n)),
+                (x, Lit(0)) | (Lit(0), x) => x,
+                (Lit(n), x) => add(x, Lit(n)).simplify(),
+                (Add(x, m), Lit(n)) if matches!(*m, Lit(_)) => add(*x, add(*m, Lit(n))).simplify(),
+                (Add(x, m), y) if matches!(*m, Lit(_)) => add(add(*x, y), *m).simplify(),
+                (x, Add(y, m)) if matches!(*m, Lit(_)) => add(add(x, *y), *m).simplify(),
+                (x, y) if x == y => mul(Lit(2), x),
+                (Mul(k, x), y) if matches!(*k, Lit(_)) && *x == y => {
+                    mul(add(*k, Lit(1)), y).simplify()
+                }
+                (x, y) => add(x, y),
+            },
+            Mul(a, b) => match (a.simplify(), b.simplify()) {
+                (Lit(0), _) | (_, Lit(0)) => Lit(0),
+                (Lit(1), x) | (x, Lit(1)) => x,
+                (x, Factorial(y)) | (Factorial(y), x)
+                    if *y == add(x.clone(), Lit(-1)).simplify() =>
+                {
+                    Factorial(Box::new(x))
+                }
+                (x, y) => mul(x, y),
+            },
+            Factorial(a) => Factorial(Box::new(a.simplify())),
+            other => other,
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Add(..) => 0,
+            Expr::Mul(..) => 1,
+            _ => 2,
+        }
+    }
+
+    fn fmt_at(&self, f: &mut std::fmt::Formatter<'_>, precedence: u8) -> std::fmt::Result {
+        if self.precedence() < precedence {
+            write!(f, "(")?;
+            self.fmt_at(f, 0)?;
+            return write!(f, ")");
+        }
+        match self {
+            Expr::Lit(n) => write!(f, "{n}"),
+            Expr::Reg(r) => write!(f, "{r}"),
+            Expr::Add(a, b) => match **b {
+                Expr::Lit(n) if n < 0 => {
+                    a.fmt_at(f, 0)?;
+                    write!(f, " - {}", n.unsigned_abs())
+                }
+                _ => {
+                    a.fmt_at(f, 0)?;
+                    write!(f, " + ")?;
+                    b.fmt_at(f, 1)
+                }
+            },
+            Expr::Mul(a, b) => {
+                a.fmt_at(f, 1)?;
+                write!(f, " * ")?;
+                b.fmt_at(f, 2)
+            }
+            Expr::Factorial(a) => {
+                a.fmt_at(f, 2)?;
+                write!(f, "!")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_at(f, 0)
+    }
+}
+
+// Replace jumps and toggles by a register with literals, where a `cpy` earlier
+// in the same straight run of code pins the register down.
+pub(super) fn resolve(program: &[Instruction]) -> Vec<Instruction> {
+    let mut code = program.to_vec();
+    loop {
+        let mut leaders = BTreeSet::new();
+        for (pc, instruction) in code.iter().enumerate() {
+            if let Instruction::Jnz(_, Value::Literal(offset)) = instruction {
+                // A jump that overflows leaves the program, so leads nowhere
+                if let Some(addr) = (pc as i32).checked_add(*offset) {
+                    leaders.insert(addr);
+                }
+            }
+        }
+        let mut known: HashMap<Register, i32> = HashMap::new();
+        let mut changed = false;
+        for (pc, instruction) in code.iter_mut().enumerate() {
+            if leaders.contains(&(pc as i32)) {
+                known.clear();
+            }
+            let pinned = |v: Value, known: &HashMap<Register, i32>| match v {
+                Value::Register(r) => known.get(&r).map_or(v, |&n| Value::Literal(n)),
+                v => v,
+            };
+            let resolved = match *instruction {
+                Instruction::Jnz(test, jump) => Instruction::Jnz(test, pinned(jump, &known)),
+                Instruction::Tgl(offset) => Instruction::Tgl(pinned(offset, &known)),
+                other => other,
+            };
+            changed |= resolved != *instruction;
+            *instruction = resolved;
+            match resolved {
+                Instruction::Cpy(src, Value::Register(r)) => match pinned(src, &known) {
+                    Value::Literal(n) => known.insert(r, n),
+                    _ => known.remove(&r),
+                },
+                Instruction::Inc(Value::Register(r))
+                | Instruction::Dec(Value::Register(r))
+                | Instruction::Rcv(Value::Register(r))
+                | Instruction::Add(_, Value::Register(r))
+                | Instruction::Mul(_, Value::Register(r)) => known.remove(&r),
+                Instruction::Jnz(..) => {
+                    known.clear();
+                    None
+                }
+                _ => None,
+            };
+        }
+        if !changed {
+            return code;
+        }
+    }
+}
+
+struct Lifter {
+    // The program, plus any toggles a summarised loop is known to have made
+    program: Vec<Instruction>,
+    code: Vec<Instruction>,
+    fused: Vec<Option<Fused>>,
+    labels: BTreeSet<usize>,
+    gotos: BTreeSet<usize>,
+}
+
+impl Lifter {
+    fn new(program: &[Instruction], labels: BTreeSet<usize>) -> Self {
+        let mut lifter = Lifter {
+            program: program.to_vec(),
+            code: vec![],
+            fused: vec![],
+            labels,
+            gotos: BTreeSet::new(),
+        };
+        lifter.recompile();
+        lifter
+    }
+
+    fn recompile(&mut self) {
+        self.code = resolve(&self.program);
+        self.fused = optimize(&self.code);
+    }
+
+    fn test(v: Value) -> Option<Option<Register>> {
+        match v {
+            Value::Literal(0) => None,
+            Value::Literal(_) => Some(None),
+            Value::Register(r) => Some(Some(r)),
+        }
+    }
+
+    fn goto(&mut self, unless_zero: Option<Register>, addr: usize) -> Stmt {
+        self.gotos.insert(addr);
+        Stmt::Goto { unless_zero, addr }
+    }
+
+    fn block(&mut self, start: usize, end: usize) -> Vec<Stmt> {
+        let mut stmts = vec![];
+        let mut pc = start;
+        while pc < end {
+            // A loop body starts at the same pc as the loop, so only label once
+            if self.labels.remove(&pc) {
+                stmts.push(Stmt::Label(pc));
+            }
+
+            let back = (pc..end).rev().find(|&j| match self.code[j] {
+                Instruction::Jnz(test, Value::Literal(offset)) => {
+                    test != Value::Literal(0) && (j as i32).checked_add(offset) == Some(pc as i32)
+                }
+                _ => false,
+            });
+            let fused = self.fused[pc].filter(|f| pc + f.len() as usize <= end);
+
+            if let Some(f) = fused.filter(|f| back.is_none_or(|j| j < pc + f.len() as usize)) {
+                stmts.extend(match f {
+                    Fused::Add { dest, counter } => vec![
+                        Stmt::Assign(dest, add(Expr::Reg(dest), Expr::Reg(counter))),
+                        Stmt::Assign(counter, Expr::Lit(0)),
+                    ],
+                    Fused::Mul {
+                        dest,
+                        src,
+                        scratch,
+                        counter,
+                    } => vec![
+                        Stmt::Assign(
+                            dest,
+                            add(Expr::Reg(dest), mul(lit(src), Expr::Reg(counter))),
+                        ),
+                        Stmt::Assign(scratch, Expr::Lit(0)),
+                        Stmt::Assign(counter, Expr::Lit(0)),
+                    ],
+                });
+                pc += f.len() as usize;
+                continue;
+            }
+
+            if let Some(j) = back {
+                let body = self.block(pc, j);
+                let until = match self.code[j] {
+                    Instruction::Jnz(Value::Register(r), _) => Until::Zero(r),
+                    _ if self.code[pc..j]
+                        .iter()
+                        .any(|i| matches!(i, Instruction::Tgl(_))) =>
+                    {
+                        Until::Toggled(j)
+                    }
+                    _ => Until::Never,
+                };
+                match until {
+                    Until::Toggled(j) => match self.summarise(&body, j) {
+                        Some(summary) => {
+                            stmts.extend(summary);
+                            // The toggled jump falls through to whatever it became
+                            pc = j;
+                        }
+                        None => {
+                            stmts.push(Stmt::Loop { body, until });
+                            pc = j + 1;
+                        }
+                    },
+                    _ => {
+                        stmts.push(Stmt::Loop { body, until });
+                        pc = j + 1;
+                    }
+                }
+                continue;
+            }
+
+            match self.code[pc] {
+                Instruction::Cpy(src, Value::Register(r)) => stmts.push(Stmt::Assign(r, lit(src))),
+                Instruction::Inc(Value::Register(r)) => {
+                    stmts.push(Stmt::Assign(r, add(Expr::Reg(r), Expr::Lit(1))))
+                }
+                Instruction::Dec(Value::Register(r)) => {
+                    stmts.push(Stmt::Assign(r, add(Expr::Reg(r), Expr::Lit(-1))))
+                }
+                Instruction::Tgl(offset) => {
+                    stmts.push(Stmt::Toggle(add(lit(offset), Expr::Lit(pc as i32))))
+                }
+                Instruction::Out(v) => stmts.push(Stmt::Out(lit(v))),
+                Instruction::Rcv(Value::Register(r)) => stmts.push(Stmt::Receive(r)),
+                Instruction::Add(src, Value::Register(r)) => {
+                    stmts.push(Stmt::Assign(r, add(Expr::Reg(r), lit(src))))
+                }
+                Instruction::Mul(src, Value::Register(r)) => {
+                    stmts.push(Stmt::Assign(r, mul(Expr::Reg(r), lit(src))))
+                }
+                Instruction::Jnz(test, jump) => match (Self::test(test), jump) {
+                    (None, _) => (),
+                    (Some(unless_zero), Value::Register(offset)) => stmts.push(Stmt::JumpBy {
+                        unless_zero,
+                        pc,
+                        offset,
+                    }),
+                    (Some(unless_zero), Value::Literal(offset)) => {
+                        let target = (pc as i32).checked_add(offset).unwrap_or(i32::MAX);
+                        match unless_zero {
+                            Some(zero) if target > pc as i32 + 1 && target <= end as i32 => {
+                                let body = self.block(pc + 1, target as usize);
+                                stmts.push(Stmt::If { zero, body });
+                                pc = target as usize;
+                                continue;
+                            }
+                            _ => {
+                                let addr = target.clamp(0, self.code.len() as i32) as usize;
+                                let goto = self.goto(unless_zero, addr);
+                                stmts.push(goto);
+                            }
+                        }
+                    }
+                },
+                // Aimed at literals, so do nothing
+                Instruction::Cpy(..)
+                | Instruction::Inc(_)
+                | Instruction::Dec(_)
+                | Instruction::Rcv(_)
+                | Instruction::Add(..)
+                | Instruction::Mul(..)
+                | Instruction::Nop => (),
+            }
+            pc += 1;
+        }
+        stmts
+    }
+
+    // A loop that runs `x *= y; y -= 1` until its own toggle turns the jump
+    // back into a `cpy` leaves x * y! behind.  The toggles on the way out can
+    // rewrite code after the loop too, so apply those before lifting the rest.
+    fn summarise(&mut self, body: &[Stmt], jump: usize) -> Option<Vec<Stmt>> {
+        let mut env = BTreeMap::new();
+        let mut toggles = vec![];
+        for stmt in body {
+            match stmt {
+                Stmt::Assign(r, e) => {
+                    let e = e.subst(&env).simplify();
+                    env.insert(*r, e);
+                }
+                Stmt::Toggle(e) => toggles.push(e.subst(&env).simplify()),
+                _ => return None,
+            }
+        }
+        let [toggle] = &toggles[..] else {
+            return None;
+        };
+
+        let (x, y) = env.iter().find_map(|(&x, e)| match e {
+            Expr::Mul(a, b) => match (&**a, &**b) {
+                (Expr::Reg(p), Expr::Reg(q)) | (Expr::Reg(q), Expr::Reg(p)) if *p == x => {
+                    (env.get(q) == Some(&add(Expr::Reg(*q), Expr::Lit(-1)))).then_some((x, *q))
+                }
+                _ => None,
+            },
+            _ => None,
+        })?;
+
+        // The last time round is the one that toggles the jump
+        let (k, m) = toggle.linear(y)?;
+        if k <= 0 || (jump as i64 - m) % k != 0 {
+            return None;
+        }
+        let last = (jump as i64 - m) / k;
+        if !(1..=2).contains(&last) {
+            return None;
+        }
+
+        let mut summary = vec![];
+        let mut after = vec![];
+        for (&r, e) in &env {
+            if r == x || r == y {
+                continue;
+            }
+            let e = e
+                .subst(&BTreeMap::from([(y, Expr::Lit(last as i32))]))
+                .simplify();
+            if !matches!(e, Expr::Lit(_)) {
+                return None;
+            }
+            after.push(Stmt::Assign(r, e));
+        }
+
+        // Earlier rounds, with larger y, toggle the code after the jump
+        let mut first = last;
+        let mut addr = jump as i64;
+        while addr < self.program.len() as i64 {
+            self.program[addr as usize] = self.program[addr as usize].toggled();
+            first += 1;
+            addr = k * first + m;
+        }
+        if first - 1 > last {
+            summary.push(Stmt::Assume(Expr::Reg(y), first as i32 - 1));
+        }
+        self.recompile();
+
+        summary.push(Stmt::Assign(
+            x,
+            mul(Expr::Reg(x), Expr::Factorial(Box::new(Expr::Reg(y)))),
+        ));
+        summary.push(Stmt::Assign(y, Expr::Lit(last as i32 - 1)));
+        summary.extend(after);
+        Some(summary)
+    }
+}
+
+// Write out pending assignments, holding each back while another still needs
+// the old value.  Whatever is left over is a cycle, like a swap.
+fn flush(env: &mut BTreeMap<Register, Expr>, out: &mut Vec<Stmt>) {
+    env.retain(|r, e| *e != Expr::Reg(*r));
+    while let Some(r) = env
+        .keys()
+        .copied()
+        .find(|&r| env.iter().all(|(s, e)| *s == r || !e.reads(r)))
+    {
+        let e = env.remove(&r).unwrap();
+        out.push(Stmt::Assign(r, e));
+    }
+    if !env.is_empty() {
+        out.push(Stmt::Parallel(std::mem::take(env).into_iter().collect()));
+    }
+}
+
+// Fold runs of straight-line code down to one assignment per register
+fn fold(stmts: Vec<Stmt>) -> Vec<Stmt> {
+    let mut env = BTreeMap::new();
+    let mut out = vec![];
+    for stmt in stmts {
+        match stmt {
+            Stmt::Assign(r, e) => {
+                let e = e.subst(&env).simplify();
+                env.insert(r, e);
+            }
+            Stmt::Out(e) => out.push(Stmt::Out(e.subst(&env).simplify())),
+            Stmt::Toggle(e) => out.push(Stmt::Toggle(e.subst(&env).simplify())),
+            Stmt::Assume(e, n) => out.push(Stmt::Assume(e.subst(&env).simplify(), n)),
+            Stmt::Loop { body, until } => {
+                flush(&mut env, &mut out);
+                out.push(Stmt::Loop {
+                    body: fold(body),
+                    until,
+                });
+            }
+            Stmt::If { zero, body } => {
+                flush(&mut env, &mut out);
+                out.push(Stmt::If {
+                    zero,
+                    body: fold(body),
+                });
+            }
+            other => {
+                flush(&mut env, &mut out);
+                out.push(other);
+            }
+        }
+    }
+    flush(&mut env, &mut out);
+    out
+}
+
+pub fn lift(program: &[Instruction]) -> Vec<Stmt> {
+    // Gotos are only known once the structure is, so label them second time
+    let mut first = Lifter::new(program, BTreeSet::new());
+    first.block(0, program.len());
+    let mut lifter = Lifter::new(program, first.gotos);
+    fold(lifter.block(0, program.len()))
+}
+
+fn render(stmts: &[Stmt], depth: usize, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    for stmt in stmts {
+        match stmt {
+            Stmt::Assign(r, Expr::Add(a, b)) if **a == Expr::Reg(*r) => match **b {
+                Expr::Lit(n) if n < 0 => *out += &format!("{indent}{r} -= {}\n", n.unsigned_abs()),
+                _ => *out += &format!("{indent}{r} += {b}\n"),
+            },
+            Stmt::Assign(r, e) => *out += &format!("{indent}{r} = {e}\n"),
+            Stmt::Parallel(assigns) => {
+                *out += &format!(
+                    "{indent}{} = {}\n",
+                    assigns.iter().map(|(r, _)| r).join(", "),
+                    assigns.iter().map(|(_, e)| e).join(", ")
+                )
+            }
+            Stmt::Out(e) => *out += &format!("{indent}out({e})\n"),
+            Stmt::Receive(r) => *out += &format!("{indent}{r} = receive()\n"),
+            Stmt::Toggle(e) => *out += &format!("{indent}toggle({e})\n"),
+            Stmt::Loop { body, until } => {
+                *out += &format!(
+                    "{indent}{}\n",
+                    if *until == Until::Never {
+                        "loop {"
+                    } else {
+                        "do {"
+                    }
+                );
+                render(body, depth + 1, out);
+                *out += &match until {
+                    Until::Zero(r) => format!("{indent}}} while {r} != 0\n"),
+                    Until::Never => format!("{indent}}}\n"),
+                    Until::Toggled(j) => format!("{indent}}} until toggled({j})\n"),
+                };
+            }
+            Stmt::If { zero, body } => {
+                *out += &format!("{indent}if {zero} == 0 {{\n");
+                render(body, depth + 1, out);
+                *out += &format!("{indent}}}\n");
+            }
+            Stmt::Label(addr) => *out += &format!("L{addr}:\n"),
+            Stmt::Goto { unless_zero, addr } => match unless_zero {
+                Some(r) => *out += &format!("{indent}if {r} != 0 goto L{addr}\n"),
+                None => *out += &format!("{indent}goto L{addr}\n"),
+            },
+            Stmt::JumpBy {
+                unless_zero,
+                pc,
+                offset,
+            } => match unless_zero {
+                Some(r) => *out += &format!("{indent}if {r} != 0 goto {pc} + {offset}\n"),
+                None => *out += &format!("{indent}goto {pc} + {offset}\n"),
+            },
+            Stmt::Assume(e, n) => *out += &format!("{indent}// assuming {e} >= {n}\n"),
+        }
+    }
+}
+
+pub fn decompile(program: &[Instruction]) -> String {
+    let mut out = String::new();
+    render(&lift(program), 0, &mut out);
+    out
+}
+
+#[test]
+fn test_decompile_day12() {
+    assert_eq!(
+        decompile(&super::parse(include_str!("../../input/2016/day12.txt"))),
+        "\
+a = 1
+b = 1
+d = 26
+if c == 0 {
+    goto L9
+}
+c = 0
+d += 7
+L9:
+do {
+    c = a
+    d -= 1
+    a, b = a + b, a
+} while d != 0
+a += 14 * 13
+c = 0
+d = 0
+"
+    );
+}
+
+#[test]
+fn test_decompile_day23() {
+    let program = super::parse(include_str!("../../input/2016/day23.txt"));
+    assert_eq!(
+        decompile(&program),
+        "\
+// assuming a - 1 >= 5
+a = a! + 80 * 76
+b = 1
+c = 0
+d = 0
+"
+    );
+
+    // Once it has run, the toggles have unrolled the loop
+    let mut cpu = super::Cpu::new(program);
+    cpu.set('a', 7).unwrap();
+    cpu.run();
+    let snapshot = decompile(cpu.program());
+    assert!(!snapshot.contains("toggled"));
+    assert!(snapshot.contains("a = (a - 1) * a + 80 * 76"));
+}
+
+#[test]
+fn test_decompile_day25() {
+    let source = decompile(&super::parse(include_str!("../../input/2016/day25.txt")));
+    assert!(source.starts_with("b = 0\nc = 0\nd = a + 282 * 9\nloop {\n    a = d\n"));
+    assert!(source.contains("        out(b)\n    } while a != 0\n}\n"));
+}
+
+#[test]
+fn test_decompile_extremes() {
+    // Jumps past the end of an i32 go out of the program, and sums wrap
+    let program = super::parse("cpy 2147483647 a\ninc a\njnz 1 2147483647\njnz a -2147483648");
+    let source = decompile(&program);
+    assert!(source.contains("a = -2147483648\ngoto L4\n"), "{source}");
+    let g = super::FlowGraph::new(&program);
+    assert!(g
+        .edges
+        .iter()
+        .any(|e| e.from == 0 && e.to == super::flow::Target::Exit));
+
+    // Subtracting i32::MIN can't be written by negating it
+    let program = super::parse("add -2147483648 a");
+    assert_eq!(decompile(&program), "a -= 2147483648\n");
+    let program = super::parse("add 2147483647 a\nadd 1 a\nout a");
+    assert_eq!(
+        decompile(&program),
+        "out(a - 2147483648)\na -= 2147483648\n"
+    );
+}
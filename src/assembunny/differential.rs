@@ -0,0 +1,350 @@
+use itertools::Itertools;
+
+use super::{disassemble, Arithmetic, Backend, Cpu, Instruction, Register, Stop, Value, REGISTERS};
+
+// Ticks a generated program may take on the plain interpreter, and so the
+// most any engine should need to agree with it
+const FUEL: u64 = 10_000;
+
+// A way of running a program that should be indistinguishable from plain
+// `Cpu::tick`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Engine {
+    Interpreter,
+    Optimized,
+    Compiled,
+    OptimizedCompiled,
+    // Checked arithmetic, with every register starting a few short of
+    // overflowing so the small programs made up here can reach it.  That
+    // makes them only comparable with each other.
+    Checked,
+    OptimizedChecked,
+}
+
+impl Engine {
+    pub const ALL: [Engine; 4] = [
+        Engine::Interpreter,
+        Engine::Optimized,
+        Engine::Compiled,
+        Engine::OptimizedCompiled,
+    ];
+
+    pub const CHECKED: [Engine; 2] = [Engine::Checked, Engine::OptimizedChecked];
+
+    pub fn cpu(self, program: Vec<Instruction>) -> Cpu {
+        let mut cpu = Cpu::new(program);
+        if matches!(
+            self,
+            Engine::Optimized | Engine::OptimizedCompiled | Engine::OptimizedChecked
+        ) {
+            cpu.optimize();
+        }
+        if matches!(self, Engine::Compiled | Engine::OptimizedCompiled) {
+            cpu.set_backend(Backend::Compiled);
+        }
+        if Engine::CHECKED.contains(&self) {
+            cpu.set_arithmetic(Arithmetic::Checked);
+            for r in REGISTERS {
+                cpu.set(r, i64::MAX - 3).unwrap();
+            }
+        }
+        cpu
+    }
+
+    pub fn run(self, program: &[Instruction]) -> Outcome {
+        let mut cpu = self.cpu(program.to_vec());
+        let mut fuel = FUEL;
+        let mut output = vec![];
+        loop {
+            match cpu.run_with_fuel(&mut fuel) {
+                Stop::Output(v) => output.push(v),
+                stop => {
+                    return Outcome {
+                        registers: cpu.registers().collect(),
+                        output,
+                        stop,
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Everything about a run that two engines have to agree on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    pub registers: Vec<(Register, i64)>,
+    pub output: Vec<i64>,
+    pub stop: Stop,
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let registers = self.registers.iter().map(|(r, v)| format!("{r}={v}"));
+        write!(
+            f,
+            "{:?} with {} output [{}]",
+            self.stop,
+            registers.format(" "),
+            self.output.iter().format(" ")
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub program: Vec<Instruction>,
+    pub left: (Engine, Outcome),
+    pub right: (Engine, Outcome),
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ((left, a), (right, b)) = (&self.left, &self.right);
+        write!(f, "{}", disassemble(&self.program))?;
+        writeln!(f, "{left:?}: {a}")?;
+        writeln!(f, "{right:?}: {b}")
+    }
+}
+
+// xorshift64*, which is plenty for making up programs
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn range(&mut self, lo: i32, hi: i32) -> i32 {
+        lo + self.below((hi - lo + 1) as usize) as i32
+    }
+
+    fn register(&mut self) -> Register {
+        REGISTERS[self.below(REGISTERS.len())]
+    }
+
+    fn value(&mut self) -> Value {
+        match self.below(3) {
+            0 => Value::Literal(self.range(-3, 3)),
+            _ => Value::Register(self.register()),
+        }
+    }
+}
+
+// Loops only ever count a register down from a small literal, but a toggle
+// can still turn one into something that spins, so `random_program` checks
+// each candidate actually halts.
+fn generate(rng: &mut Rng, program: &mut Vec<Instruction>, len: usize, depth: usize) {
+    let target = program.len() + len;
+    while program.len() < target {
+        match rng.below(12) {
+            0..=2 => program.push(Instruction::Cpy(rng.value(), rng.value())),
+            3 | 4 => program.push(Instruction::Inc(rng.value())),
+            5 => program.push(Instruction::Dec(rng.value())),
+            6 => program.push(Instruction::Out(rng.value())),
+            7 => program.push(Instruction::Tgl(rng.value())),
+            8 => program.push(Instruction::Jnz(
+                rng.value(),
+                Value::Literal(rng.range(0, 3)),
+            )),
+            _ if depth < 2 => {
+                let counter = rng.register();
+                program.push(Instruction::Cpy(
+                    Value::Literal(rng.range(1, 4)),
+                    Value::Register(counter),
+                ));
+                let start = program.len();
+                let body = 1 + rng.below(3);
+                generate(rng, program, body, depth + 1);
+                program.push(Instruction::Dec(Value::Register(counter)));
+                let back = start as i32 - program.len() as i32;
+                program.push(Instruction::Jnz(
+                    Value::Register(counter),
+                    Value::Literal(back),
+                ));
+            }
+            _ => program.push(Instruction::Inc(Value::Register(rng.register()))),
+        }
+    }
+}
+
+fn halts(program: &[Instruction]) -> bool {
+    Engine::Interpreter.run(program).stop == Stop::Halted
+}
+
+// A program that halts within the fuel limit on the plain interpreter.
+// Generating one that's sure to halt would rule out toggling a loop's jump,
+// so this doesn't terminate by construction: candidates are tried until one
+// halts.  Any candidate might come out as nothing but `inc`s, so it does
+// always end.
+pub fn random_program(seed: u64) -> Vec<Instruction> {
+    let mut rng = Rng::new(seed);
+    loop {
+        let mut program = vec![];
+        let len = 1 + rng.below(12);
+        generate(&mut rng, &mut program, len, 0);
+        if halts(&program) {
+            return program;
+        }
+    }
+}
+
+pub fn compare(program: &[Instruction], left: Engine, right: Engine) -> Option<Divergence> {
+    let (a, b) = (left.run(program), right.run(program));
+    (a != b).then(|| Divergence {
+        program: program.to_vec(),
+        left: (left, a),
+        right: (right, b),
+    })
+}
+
+// Drop the instruction at `pc`, keeping jumps across it aimed where they were
+fn remove(program: &[Instruction], pc: usize) -> Vec<Instruction> {
+    let shift = |addr: i64| addr - (addr > pc as i64) as i64;
+    let mut smaller = program.to_vec();
+    smaller.remove(pc);
+    for (at, instruction) in smaller.iter_mut().enumerate() {
+        let from = at as i64 + (at >= pc) as i64;
+        if let Instruction::Jnz(test, Value::Literal(offset)) = *instruction {
+            let offset = shift(from + offset as i64) - at as i64;
+            *instruction = Instruction::Jnz(test, Value::Literal(offset as i32));
+        }
+    }
+    smaller
+}
+
+// The instruction with each literal in turn moved towards zero
+fn smaller_literals(instruction: Instruction) -> Vec<Instruction> {
+    use Instruction::*;
+    let smaller = |v: Value| match v {
+        Value::Literal(n) if n != 0 => [0, n / 2, n - n.signum()]
+            .into_iter()
+            .dedup()
+            .map(Value::Literal)
+            .collect(),
+        _ => vec![],
+    };
+    match instruction {
+        Cpy(a, b) => (smaller(a).into_iter().map(|a| Cpy(a, b)))
+            .chain(smaller(b).into_iter().map(|b| Cpy(a, b)))
+            .collect(),
+        Jnz(a, b) => (smaller(a).into_iter().map(|a| Jnz(a, b)))
+            .chain(smaller(b).into_iter().map(|b| Jnz(a, b)))
+            .collect(),
+        Inc(a) => smaller(a).into_iter().map(Inc).collect(),
+        Dec(a) => smaller(a).into_iter().map(Dec).collect(),
+        Tgl(a) => smaller(a).into_iter().map(Tgl).collect(),
+        Out(a) => smaller(a).into_iter().map(Out).collect(),
+        _ => vec![],
+    }
+}
+
+// Simpler programs to try in place of one that fails: runs of instructions
+// cut out, longest first, then single instructions dropped from around the
+// jumps, then smaller literals.  Each is shorter, or has a literal nearer
+// zero, so shrinking always comes to an end.
+fn simpler(program: &[Instruction]) -> Vec<Vec<Instruction>> {
+    let mut candidates = vec![];
+    for len in (1..=program.len()).rev() {
+        for start in 0..=program.len() - len {
+            let mut smaller = program.to_vec();
+            smaller.drain(start..start + len);
+            candidates.push(smaller);
+        }
+    }
+    candidates.extend((0..program.len()).map(|pc| remove(program, pc)));
+    for (pc, &instruction) in program.iter().enumerate() {
+        candidates.extend(smaller_literals(instruction).into_iter().map(|i| {
+            let mut smaller = program.to_vec();
+            smaller[pc] = i;
+            smaller
+        }));
+    }
+    candidates
+}
+
+// Simplify the program for as long as it still halts and still shows the
+// problem
+fn shrink(
+    mut program: Vec<Instruction>,
+    fails: impl Fn(&[Instruction]) -> bool,
+) -> Vec<Instruction> {
+    while let Some(smaller) = simpler(&program).into_iter().find(|p| halts(p) && fails(p)) {
+        program = smaller;
+    }
+    program
+}
+
+// Run `cases` random programs on both engines, giving back the smallest
+// program found that they disagree on
+pub fn differential(
+    left: Engine,
+    right: Engine,
+    seed: u64,
+    cases: u64,
+) -> Result<(), Box<Divergence>> {
+    for case in 0..cases {
+        let program = random_program(seed.wrapping_add(case));
+        if compare(&program, left, right).is_some() {
+            let smallest = shrink(program, |p| compare(p, left, right).is_some());
+            return Err(Box::new(compare(&smallest, left, right).unwrap()));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_engines_agree() {
+    for engine in &Engine::ALL[1..] {
+        if let Err(divergence) = differential(Engine::Interpreter, *engine, 2016, 500) {
+            panic!("\n{divergence}");
+        }
+    }
+    if let Err(divergence) = differential(Engine::Checked, Engine::OptimizedChecked, 2016, 500) {
+        panic!("\n{divergence}");
+    }
+}
+
+#[test]
+fn test_random_programs() {
+    let programs = (0..200).map(random_program).collect::<Vec<_>>();
+    assert!(programs.iter().all(|p| halts(p)));
+    // Enough variety to reach the corners: toggles, outputs, literal targets
+    // and loops the optimizer fuses
+    let all = programs.concat();
+    assert!(all.iter().any(|i| matches!(i, Instruction::Tgl(_))));
+    assert!(all.iter().any(|i| matches!(i, Instruction::Out(_))));
+    assert!(all
+        .iter()
+        .any(|i| matches!(i, Instruction::Cpy(_, Value::Literal(_)))));
+    assert!(programs
+        .iter()
+        .any(|p| super::optimize::optimize(p).iter().any(Option::is_some)));
+}
+
+#[test]
+fn test_shrink() {
+    let program = super::parse("cpy 2 b\ninc c\nout c\ncpy 3 a\ndec a\njnz a -1");
+    let outputs_one = |p: &[Instruction]| Engine::Interpreter.run(p).output == [1];
+    assert_eq!(shrink(program, outputs_one), super::parse("inc c\nout c"));
+
+    // Cutting `inc c` alone would leave the jump aimed at the `cpy`, and
+    // the loop only needs to go round twice
+    let program = super::parse("cpy 3 a\ninc b\ninc c\ndec a\njnz a -3\nout b");
+    let outputs_two = |p: &[Instruction]| Engine::Interpreter.run(p).output == [2];
+    assert_eq!(
+        shrink(program, outputs_two),
+        super::parse("cpy 2 a\ninc b\ndec a\njnz a -2\nout b")
+    );
+}
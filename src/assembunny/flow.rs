@@ -0,0 +1,297 @@
+use std::collections::BTreeSet;
+
+use super::{Instruction, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Target {
+    Block(usize),
+    // Anywhere outside the program, which halts (or faults)
+    Exit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    Taken,
+    FallThrough,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: Target,
+    pub kind: EdgeKind,
+}
+
+// Instructions `start..end`, only ever entered at the top
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NaturalLoop {
+    pub header: usize,
+    pub body: BTreeSet<usize>,
+}
+
+// The control flow of a program as it stands; a `tgl` can change it at run
+// time, so every instruction one might reach is listed in `toggle_targets`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlowGraph {
+    pub program: Vec<Instruction>,
+    pub blocks: Vec<Block>,
+    pub edges: Vec<Edge>,
+    pub loops: Vec<NaturalLoop>,
+    // `jnz`s whose offset is a register, so have no taken edge
+    pub dynamic_jumps: Vec<usize>,
+    pub toggle_targets: BTreeSet<usize>,
+}
+
+impl FlowGraph {
+    pub fn new(program: &[Instruction]) -> Self {
+        let len = program.len();
+
+        let mut leaders = BTreeSet::from([0]);
+        for (pc, instruction) in program.iter().enumerate() {
+            if let Instruction::Jnz(_, jump) = instruction {
+                leaders.insert(pc + 1);
+                if let Value::Literal(offset) = jump {
+                    let addr = (pc as i32).checked_add(*offset).unwrap_or(i32::MAX);
+                    leaders.insert(addr.clamp(0, len as i32) as usize);
+                }
+            }
+        }
+        leaders.insert(len);
+        let blocks = leaders
+            .iter()
+            .zip(leaders.iter().skip(1))
+            .map(|(&start, &end)| Block { start, end })
+            .collect::<Vec<_>>();
+        let block_of = |addr: i32| {
+            blocks
+                .iter()
+                .position(|b| (b.start as i32..b.end as i32).contains(&addr))
+                .map_or(Target::Exit, Target::Block)
+        };
+
+        let mut edges = vec![];
+        let mut dynamic_jumps = vec![];
+        for (from, block) in blocks.iter().enumerate() {
+            let pc = block.end - 1;
+            let (taken, falls) = match program[pc] {
+                Instruction::Jnz(Value::Literal(0), _) => (None, true),
+                Instruction::Jnz(Value::Literal(_), jump) => (Some(jump), false),
+                Instruction::Jnz(Value::Register(_), jump) => (Some(jump), true),
+                _ => (None, true),
+            };
+            match taken {
+                Some(Value::Literal(offset)) => edges.push(Edge {
+                    from,
+                    to: (pc as i32)
+                        .checked_add(offset)
+                        .map_or(Target::Exit, block_of),
+                    kind: EdgeKind::Taken,
+                }),
+                Some(Value::Register(_)) => dynamic_jumps.push(pc),
+                None => (),
+            }
+            if falls {
+                edges.push(Edge {
+                    from,
+                    to: block_of(block.end as i32),
+                    kind: EdgeKind::FallThrough,
+                });
+            }
+        }
+
+        // Without knowing register values, a `tgl` by register could hit
+        // anything
+        let toggle_targets = program
+            .iter()
+            .enumerate()
+            .flat_map(|(pc, instruction)| match instruction {
+                Instruction::Tgl(Value::Literal(offset)) => (pc as i32)
+                    .checked_add(*offset)
+                    .filter(|addr| (0..len as i32).contains(addr))
+                    .map(|addr| addr as usize..addr as usize + 1),
+                Instruction::Tgl(Value::Register(_)) => Some(0..len),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        let mut graph = FlowGraph {
+            program: program.to_vec(),
+            blocks,
+            edges,
+            loops: vec![],
+            dynamic_jumps,
+            toggle_targets,
+        };
+        graph.loops = graph.natural_loops();
+        graph
+    }
+
+    fn successors(&self, block: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |e| e.from == block)
+            .filter_map(|e| match e.to {
+                Target::Block(b) => Some(b),
+                Target::Exit => None,
+            })
+    }
+
+    fn predecessors(&self, block: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |e| e.to == Target::Block(block))
+            .map(|e| e.from)
+    }
+
+    // Blocks reachable from the entry, ignoring dynamic jumps
+    pub fn reachable(&self) -> BTreeSet<usize> {
+        let mut seen = BTreeSet::new();
+        let mut todo = vec![0];
+        while let Some(b) = todo.pop() {
+            if b < self.blocks.len() && seen.insert(b) {
+                todo.extend(self.successors(b));
+            }
+        }
+        seen
+    }
+
+    fn dominators(&self, reachable: &BTreeSet<usize>) -> Vec<BTreeSet<usize>> {
+        let mut dom = vec![reachable.clone(); self.blocks.len()];
+        dom[0] = BTreeSet::from([0]);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &b in reachable.iter().skip(1) {
+                let mut new = self
+                    .predecessors(b)
+                    .filter(|p| reachable.contains(p))
+                    .map(|p| dom[p].clone())
+                    .reduce(|a, b| &a & &b)
+                    .unwrap_or_default();
+                new.insert(b);
+                if new != dom[b] {
+                    dom[b] = new;
+                    changed = true;
+                }
+            }
+        }
+        dom
+    }
+
+    fn natural_loops(&self) -> Vec<NaturalLoop> {
+        if self.blocks.is_empty() {
+            return vec![];
+        }
+        let reachable = self.reachable();
+        let dom = self.dominators(&reachable);
+        let mut loops: Vec<NaturalLoop> = vec![];
+        for edge in &self.edges {
+            let Target::Block(header) = edge.to else {
+                continue;
+            };
+            if !reachable.contains(&edge.from) || !dom[edge.from].contains(&header) {
+                continue;
+            }
+            let mut body = BTreeSet::from([header]);
+            let mut todo = vec![edge.from];
+            while let Some(b) = todo.pop() {
+                if body.insert(b) {
+                    todo.extend(self.predecessors(b));
+                }
+            }
+            match loops.iter_mut().find(|l| l.header == header) {
+                Some(l) => l.body.extend(body),
+                None => loops.push(NaturalLoop { header, body }),
+            }
+        }
+        loops.sort_by_key(|l| l.header);
+        loops
+    }
+
+    fn is_back_edge(&self, edge: &Edge) -> bool {
+        self.loops
+            .iter()
+            .any(|l| Target::Block(l.header) == edge.to && l.body.contains(&edge.from))
+    }
+
+    // Graphviz source: loop headers are doubled, back edges blue, dynamic
+    // jumps red, and instructions a `tgl` could rewrite are marked with `*`.
+    pub fn to_dot(&self) -> String {
+        let mut out =
+            String::from("digraph assembunny {\n    node [shape=box, fontname=monospace];\n");
+        for (b, block) in self.blocks.iter().enumerate() {
+            let label = (block.start..block.end)
+                .map(|pc| {
+                    let mark = if self.toggle_targets.contains(&pc) {
+                        "*"
+                    } else {
+                        " "
+                    };
+                    format!("{pc:>3}{mark} {}\\l", self.program[pc])
+                })
+                .collect::<String>();
+            let mut attrs = format!("label=\"{label}\"");
+            if self.loops.iter().any(|l| l.header == b) {
+                attrs += ", peripheries=2";
+            }
+            if self.dynamic_jumps.contains(&(block.end - 1)) {
+                attrs += ", color=red";
+            }
+            out += &format!("    b{b} [{attrs}];\n");
+        }
+        out += "    exit [shape=doublecircle, label=\"halt\"];\n";
+        for edge in &self.edges {
+            let to = match edge.to {
+                Target::Block(b) => format!("b{b}"),
+                Target::Exit => String::from("exit"),
+            };
+            let mut attrs = vec![];
+            if edge.kind == EdgeKind::FallThrough {
+                attrs.push("style=dashed");
+            }
+            if self.is_back_edge(edge) {
+                attrs.push("color=blue");
+            }
+            out += &format!("    b{} -> {to} [{}];\n", edge.from, attrs.join(", "));
+        }
+        out + "}\n"
+    }
+}
+
+#[test]
+fn test_flow_day12() {
+    let g = FlowGraph::new(&super::parse(include_str!("../../input/2016/day12.txt")));
+    assert_eq!(g.blocks.len(), 11);
+    assert_eq!(
+        g.loops
+            .iter()
+            .map(|l| g.blocks[l.header].start)
+            .collect::<Vec<_>>(),
+        vec![6, 9, 10, 17, 18]
+    );
+    let outer = g
+        .loops
+        .iter()
+        .find(|l| g.blocks[l.header].start == 9)
+        .unwrap();
+    assert_eq!(outer.body.len(), 3);
+    assert!(g.dynamic_jumps.is_empty());
+    assert!(g.toggle_targets.is_empty());
+}
+
+#[test]
+fn test_flow_day23() {
+    let g = FlowGraph::new(&super::parse(include_str!("../../input/2016/day23.txt")));
+    assert_eq!(g.dynamic_jumps, vec![18, 20]);
+    assert_eq!(g.toggle_targets.len(), g.program.len());
+    let dot = g.to_dot();
+    assert!(dot.starts_with("digraph assembunny {"));
+    assert!(dot.contains(" 18* jnz 1 c\\l\", color=red]"));
+}
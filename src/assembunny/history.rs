@@ -0,0 +1,178 @@
+use super::{optimize, Cpu, Instruction, Register};
+
+// What one recorded tick changed, so it can be put back
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Undo {
+    pub pc: i32,
+    // Registers the tick changed, with what they held before
+    pub writes: Vec<(Register, i64)>,
+    // An instruction a `tgl` rewrote, as it was
+    pub toggled: Option<(usize, Instruction)>,
+    // A value `rcv` took from the inbox
+    pub received: Option<i64>,
+}
+
+impl Cpu {
+    // Log every tick from here on so it can be stepped back over.  Profile
+    // counts and anything already output aren't rewound.
+    pub fn enable_recording(&mut self) {
+        self.history = Some(vec![]);
+    }
+
+    pub fn recording(&self) -> bool {
+        self.history.is_some()
+    }
+
+    // One entry per tick since recording started, oldest first
+    pub fn history(&self) -> &[Undo] {
+        self.history.as_deref().unwrap_or_default()
+    }
+
+    pub(super) fn record_tick(&mut self, pc: i32) {
+        if let Some(history) = &mut self.history {
+            history.push(Undo {
+                pc,
+                ..Default::default()
+            });
+        }
+    }
+
+    // Every register write while running goes through here
+    pub(super) fn assign(&mut self, slot: usize, value: i64) {
+        let old = std::mem::replace(&mut self.registers[slot], value);
+        if let Some(undo) = self.history.as_mut().and_then(|h| h.last_mut()) {
+            if old != value {
+                undo.writes.push((self.names[slot], old));
+            }
+        }
+    }
+
+    pub(super) fn record_toggle(&mut self, addr: usize, old: Instruction) {
+        if let Some(undo) = self.history.as_mut().and_then(|h| h.last_mut()) {
+            undo.toggled = Some((addr, old));
+        }
+    }
+
+    pub(super) fn receive(&mut self) -> Option<i64> {
+        let value = self.inbox.pop_front()?;
+        if let Some(undo) = self.history.as_mut().and_then(|h| h.last_mut()) {
+            undo.received = Some(value);
+        }
+        Some(value)
+    }
+
+    // Forget the history after a change that can't be undone
+    pub(super) fn restart_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+    }
+
+    // Undo the last recorded tick, false if there's none left
+    pub fn step_back(&mut self) -> bool {
+        let Some(undo) = self.history.as_mut().and_then(Vec::pop) else {
+            return false;
+        };
+        for &(r, old) in undo.writes.iter().rev() {
+            let slot = self.slot(r).unwrap();
+            self.registers[slot] = old;
+        }
+        if let Some((addr, old)) = undo.toggled {
+            self.program[addr] = old;
+            if self.optimized {
+                self.fused = optimize::optimize(&self.program);
+            }
+            if self.ops.is_some() {
+                self.recompile(addr);
+            }
+        }
+        if let Some(value) = undo.received {
+            self.inbox.push_front(value);
+        }
+        self.pc = undo.pc;
+        self.output = None;
+        self.fault = None;
+        true
+    }
+
+    // Back to just before recorded tick `tick`, where 0 is when recording
+    // started.  False if that's still in the future.
+    pub fn rewind_to(&mut self, tick: usize) -> bool {
+        if tick > self.history().len() {
+            return false;
+        }
+        while self.history().len() > tick {
+            self.step_back();
+        }
+        true
+    }
+
+    // The recorded tick that last changed a register
+    pub fn last_change(&self, r: Register) -> Option<usize> {
+        self.history()
+            .iter()
+            .rposition(|undo| undo.writes.iter().any(|&(w, _)| w == r))
+    }
+
+    // The recorded tick whose `tgl` last rewrote the instruction at `addr`
+    pub fn last_toggle(&self, addr: usize) -> Option<usize> {
+        self.history()
+            .iter()
+            .rposition(|undo| undo.toggled.is_some_and(|(a, _)| a == addr))
+    }
+}
+
+#[test]
+fn test_step_back() {
+    for backend in [super::Backend::Interpreter, super::Backend::Compiled] {
+        let mut cpu = super::day23();
+        cpu.set_backend(backend);
+        cpu.enable_recording();
+        let mut states = vec![cpu.snapshot()];
+        while !cpu.halted() {
+            cpu.tick();
+            states.push(cpu.snapshot());
+        }
+        assert_eq!(cpu.get('a'), Ok(11120));
+        assert_eq!(cpu.history().len(), states.len() - 1);
+
+        // Every state comes back, program and all
+        while let Some(state) = states.pop() {
+            assert_eq!(cpu.snapshot(), state);
+            assert_eq!(cpu.step_back(), !states.is_empty());
+        }
+    }
+}
+
+#[test]
+fn test_time_travel() {
+    let mut cpu = super::day23();
+    cpu.optimize();
+    cpu.enable_recording();
+    cpu.run();
+    let end = cpu.snapshot();
+    let ticks = cpu.history().len();
+
+    // The last toggle of the final `jnz 1 c` is what lets the program finish
+    let toggle = cpu.last_toggle(18).unwrap();
+    assert_eq!(cpu.history()[toggle].pc, 16);
+    assert!(cpu.rewind_to(toggle));
+    assert_eq!(cpu.program()[18], "jnz 1 c".parse().unwrap());
+    assert_eq!(cpu.pc(), 16);
+
+    let change = cpu.last_change('c').unwrap();
+    assert!(change < toggle);
+
+    assert!(!cpu.rewind_to(ticks));
+    cpu.run();
+    assert_eq!(cpu.snapshot(), end);
+    assert!(cpu.rewind_to(0));
+    assert_eq!(cpu.snapshot(), super::day23().snapshot());
+
+    // Setting a register by hand starts the history again
+    cpu.run_with_fuel(&mut 100);
+    cpu.set('b', 1).unwrap();
+    assert!(cpu.history().is_empty());
+    assert!(!cpu.step_back());
+    assert_eq!(cpu.get('b'), Ok(1));
+}
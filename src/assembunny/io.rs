@@ -0,0 +1,215 @@
+use std::io::{BufRead, Write};
+
+use super::{Cpu, Stop};
+
+// Somewhere for `out` to send its values.  Turning a value away stops the run
+// there.
+pub trait Sink {
+    fn accept(&mut self, value: i64) -> bool;
+}
+
+// Where `rcv` gets its values from, once anything sent directly runs out
+pub trait Source {
+    fn next_value(&mut self) -> Option<i64>;
+}
+
+// Collects everything
+impl Sink for Vec<i64> {
+    fn accept(&mut self, value: i64) -> bool {
+        self.push(value);
+        true
+    }
+}
+
+// Checks the output follows a repeating pattern, stopping at the first value
+// that doesn't, or once `limit` values have matched
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    pattern: Vec<i64>,
+    limit: usize,
+    matched: usize,
+    mismatch: Option<i64>,
+}
+
+impl Pattern {
+    pub fn new(pattern: &[i64], limit: usize) -> Self {
+        Pattern {
+            pattern: pattern.to_vec(),
+            limit,
+            matched: 0,
+            mismatch: None,
+        }
+    }
+
+    pub fn matched(&self) -> usize {
+        self.matched
+    }
+
+    pub fn mismatch(&self) -> Option<i64> {
+        self.mismatch
+    }
+
+    // Every value so far matched, and there were enough of them
+    pub fn complete(&self) -> bool {
+        self.mismatch.is_none() && self.matched >= self.limit
+    }
+}
+
+impl Sink for Pattern {
+    fn accept(&mut self, value: i64) -> bool {
+        if self.mismatch.is_some() || self.matched >= self.limit {
+            return false;
+        }
+        if self.pattern.get(self.matched % self.pattern.len().max(1)) != Some(&value) {
+            self.mismatch = Some(value);
+            return false;
+        }
+        self.matched += 1;
+        true
+    }
+}
+
+// One value per line, to stdout, a file or anything else.  A failed write
+// stops the run, leaving the error to look at.
+pub struct Writer<W: Write> {
+    writer: W,
+    pub error: Option<std::io::Error>,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(writer: W) -> Self {
+        Writer {
+            writer,
+            error: None,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Sink for Writer<W> {
+    fn accept(&mut self, value: i64) -> bool {
+        match writeln!(self.writer, "{value}").and_then(|_| self.writer.flush()) {
+            Ok(()) => true,
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+}
+
+// Any iterator of values will do as a source
+impl<I: Iterator<Item = i64>> Source for I {
+    fn next_value(&mut self) -> Option<i64> {
+        self.next()
+    }
+}
+
+// Whitespace separated numbers, from stdin or a file, read a line at a time
+// as they're needed.  A line with anything else on it ends the input, as
+// does a read error, and nothing after it is ever read.
+pub struct Reader<R: BufRead> {
+    reader: R,
+    pending: std::collections::VecDeque<i64>,
+    finished: bool,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(reader: R) -> Self {
+        Reader {
+            reader,
+            pending: Default::default(),
+            finished: false,
+        }
+    }
+}
+
+impl<R: BufRead> Source for Reader<R> {
+    fn next_value(&mut self) -> Option<i64> {
+        while self.pending.is_empty() && !self.finished {
+            let mut line = String::new();
+            let values = match self.reader.read_line(&mut line) {
+                Ok(0) | Err(_) => None,
+                Ok(_) => line
+                    .split_ascii_whitespace()
+                    .map(|tok| tok.parse())
+                    .collect::<Result<_, _>>()
+                    .ok(),
+            };
+            match values {
+                Some(values) => self.pending = values,
+                None => self.finished = true,
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+impl Cpu {
+    // Like `run_with_fuel`, but outputs go to `sink` and a `rcv` with nothing
+    // sent draws from `source`.  Comes back with `Stop::Output` only for a
+    // value the sink turned away, and `Stop::Blocked` once the source is dry.
+    pub fn run_with_io(
+        &mut self,
+        sink: &mut impl Sink,
+        source: &mut impl Source,
+        fuel: &mut u64,
+    ) -> Stop {
+        loop {
+            if self.waiting() {
+                match source.next_value() {
+                    Some(v) => self.send(v),
+                    None => return Stop::Blocked,
+                }
+            }
+            match self.run_with_fuel(fuel) {
+                Stop::Output(v) if sink.accept(v) => (),
+                Stop::Blocked => (),
+                stop => return stop,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_sinks() {
+    let program = super::parse("cpy 5 b\nout b\ndec b\njnz b -2");
+    let cpu = || Cpu::new(program.clone());
+    let mut outputs = vec![];
+    let stop = cpu().run_with_io(&mut outputs, &mut std::iter::empty(), &mut 1000);
+    assert_eq!((stop, outputs), (Stop::Halted, vec![5, 4, 3, 2, 1]));
+
+    let mut pattern = Pattern::new(&[5, 4], 10);
+    let stop = cpu().run_with_io(&mut pattern, &mut std::iter::empty(), &mut 1000);
+    assert_eq!(stop, Stop::Output(3));
+    assert_eq!((pattern.matched(), pattern.mismatch()), (2, Some(3)));
+    assert!(!pattern.complete());
+
+    let mut pattern = Pattern::new(&[5, 4, 3], 2);
+    let stop = cpu().run_with_io(&mut pattern, &mut std::iter::empty(), &mut 1000);
+    assert_eq!(stop, Stop::Output(3));
+    assert!(pattern.complete());
+
+    let mut writer = Writer::new(vec![]);
+    cpu().run_with_io(&mut writer, &mut std::iter::empty(), &mut 1000);
+    assert_eq!(writer.into_inner(), b"5\n4\n3\n2\n1\n");
+}
+
+#[test]
+fn test_sources() {
+    // Echo doubled
+    let program = "rcv a\nmul 2 a\nout a\njnz 1 -3";
+    let mut outputs = vec![];
+    let mut cpu = Cpu::new(super::parse(program));
+    cpu.send(1);
+    let stop = cpu.run_with_io(&mut outputs, &mut [2, 3].into_iter(), &mut 1000);
+    assert_eq!((stop, &outputs), (Stop::Blocked, &vec![2, 4, 6]));
+
+    let mut reader = Reader::new("7 8\n\n9\n10 x\n11".as_bytes());
+    cpu.run_with_io(&mut outputs, &mut reader, &mut 1000);
+    assert_eq!(outputs, [2, 4, 6, 14, 16, 18]);
+    assert_eq!(reader.next_value(), None);
+}
@@ -0,0 +1,211 @@
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use super::{
+    assembler, decompile, AssemblyError, Dialect, FlowGraph, Instruction, LineError, Value,
+};
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+    #[error("`{0}` is aimed at a literal, so does nothing")]
+    LiteralTarget(Instruction),
+
+    #[error("`{0}` has a constant condition, so {}", if *.1 { "always jumps" } else { "never jumps" })]
+    ConstantCondition(Instruction, bool),
+
+    #[error("`{0}` jumps to {1}, outside the program")]
+    JumpOutside(Instruction, i64),
+
+    #[error("`{0}` can never run")]
+    Unreachable(Instruction),
+
+    #[error("`{0}` toggles {1}, outside the program")]
+    ToggleOutside(Instruction, i64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub pc: usize,
+    pub lint: Lint,
+}
+
+// Instructions that might run, going by the program as written but allowing
+// for anything a `tgl` could hit to be flipped.  None when some jump is by a
+// register, which could go anywhere.
+fn reachable(program: &[Instruction], toggles: &BTreeSet<usize>) -> Option<Vec<bool>> {
+    let len = program.len() as i64;
+    let mut seen = vec![false; program.len()];
+    let mut todo = vec![0];
+    while let Some(pc) = todo.pop() {
+        if !(0..len).contains(&pc) || seen[pc as usize] {
+            continue;
+        }
+        seen[pc as usize] = true;
+        let toggled = toggles.contains(&(pc as usize));
+        let (test, jump) = match program[pc as usize] {
+            Instruction::Jnz(test, jump) => (test, jump),
+            // Becomes a `jnz` once toggled
+            Instruction::Cpy(test, jump)
+            | Instruction::Add(test, jump)
+            | Instruction::Mul(test, jump)
+                if toggled =>
+            {
+                (test, jump)
+            }
+            _ => {
+                todo.push(pc + 1);
+                continue;
+            }
+        };
+        if test != Value::Literal(0) {
+            match jump {
+                Value::Literal(offset) => todo.push(pc + offset as i64),
+                Value::Register(_) => return None,
+            }
+        }
+        if toggled || !matches!(test, Value::Literal(n) if n != 0) {
+            todo.push(pc + 1);
+        }
+    }
+    Some(seen)
+}
+
+// Things in a program that are probably mistakes.  Instructions a `tgl` might
+// rewrite get the benefit of the doubt, as writing to a literal or jumping
+// unconditionally is often the point of them.
+pub fn lint(program: &[Instruction]) -> Vec<Warning> {
+    // A `tgl` by a literal does nothing, and one by a register set just
+    // before has a known target
+    let resolved = decompile::resolve(
+        &program
+            .iter()
+            .map(|&i| match i {
+                Instruction::Tgl(Value::Literal(_)) => Instruction::Nop,
+                i => i,
+            })
+            .collect::<Vec<_>>(),
+    );
+    let toggles = FlowGraph::new(&resolved).toggle_targets;
+    let reachable = reachable(program, &toggles);
+    let len = program.len() as i64;
+
+    let mut warnings = vec![];
+    for (pc, &instruction) in program.iter().enumerate() {
+        let mut warn = |lint| warnings.push(Warning { pc, lint });
+        if reachable.as_ref().is_some_and(|seen| !seen[pc]) {
+            warn(Lint::Unreachable(instruction));
+        }
+        let toggled = toggles.contains(&pc);
+        match instruction {
+            Instruction::Cpy(_, Value::Literal(_))
+            | Instruction::Inc(Value::Literal(_))
+            | Instruction::Dec(Value::Literal(_))
+            | Instruction::Tgl(Value::Literal(_))
+            | Instruction::Add(_, Value::Literal(_))
+            | Instruction::Mul(_, Value::Literal(_))
+                if !toggled =>
+            {
+                warn(Lint::LiteralTarget(instruction))
+            }
+            Instruction::Jnz(test, jump) => {
+                if let Value::Literal(n) = test {
+                    if !toggled {
+                        warn(Lint::ConstantCondition(instruction, n != 0));
+                    }
+                }
+                if let Value::Literal(offset) = jump {
+                    let addr = pc as i64 + offset as i64;
+                    if !(0..=len).contains(&addr) {
+                        warn(Lint::JumpOutside(instruction, addr));
+                    }
+                }
+            }
+            Instruction::Tgl(Value::Register(_)) => {
+                if let Instruction::Tgl(Value::Literal(offset)) = resolved[pc] {
+                    let addr = pc as i64 + offset as i64;
+                    if !(0..len).contains(&addr) {
+                        warn(Lint::ToggleOutside(instruction, addr));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    warnings
+}
+
+// Lint assembler source, or a puzzle listing, pointing each warning at the
+// line it came from
+pub fn lint_source(source: &str, dialect: &Dialect) -> Result<Vec<LineError<Lint>>, AssemblyError> {
+    let lines = assembler::assemble_lines(source, dialect)?;
+    let program = lines.iter().map(|&(_, i)| i).collect::<Vec<_>>();
+    Ok(lint(&program)
+        .into_iter()
+        .map(|w| LineError {
+            line: lines[w.pc].0,
+            error: w.lint,
+        })
+        .collect())
+}
+
+#[test]
+fn test_lint() {
+    let source = "\
+; a made up program with one of everything
+        cpy 3 b
+        inc 2           ; meant `inc c`
+top:    dec b
+        jnz b top
+        jnz 0 top
+        cpy -9 c
+        tgl c
+        jnz 1 done
+        out b           ; skipped over
+        jnz b 7
+done:";
+    let warnings = lint_source(source, &Dialect::default())
+        .unwrap()
+        .iter()
+        .map(|w| w.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        warnings,
+        [
+            "line 3: `inc 2` is aimed at a literal, so does nothing",
+            "line 6: `jnz 0 -2` has a constant condition, so never jumps",
+            "line 8: `tgl c` toggles -3, outside the program",
+            "line 9: `jnz 1 3` has a constant condition, so always jumps",
+            "line 10: `out b` can never run",
+            "line 11: `jnz b 7` can never run",
+            "line 11: `jnz b 7` jumps to 16, outside the program",
+        ]
+    );
+}
+
+#[test]
+fn test_lint_puzzles() {
+    // Only the unconditional jumps in day 12
+    let day12 = lint(&super::parse(include_str!("../../input/2016/day12.txt")));
+    assert!(day12
+        .iter()
+        .all(|w| matches!(w.lint, Lint::ConstantCondition(_, true))));
+    assert_eq!(day12.len(), 1);
+
+    // Day 23's `tgl c` could land anywhere, so nothing is certain
+    assert_eq!(
+        lint(&super::parse(include_str!("../../input/2016/day23.txt"))),
+        []
+    );
+
+    // A toggled `jnz` is reachable where the original wasn't
+    let program = super::parse("cpy 1 c\ntgl c\njnz 1 2\ninc a");
+    assert_eq!(lint(&program), []);
+
+    // But a `tgl` by a literal never toggles anything
+    let program = super::parse("tgl 1\njnz 1 2\ninc a");
+    assert_eq!(
+        lint(&program).iter().map(|w| w.pc).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+}
@@ -0,0 +1,152 @@
+use super::{Cpu, Fault, Stop};
+
+// Why a network run came back
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NetworkStop {
+    Halted,
+    // Every machine still running is waiting on a `rcv`
+    Deadlock(Vec<usize>),
+    Fault(usize, Fault),
+    OutOfFuel,
+}
+
+// Machines whose outputs feed the inputs of others.  They take turns of up to
+// `quantum` ticks in a fixed order, so a run always plays out the same way.
+pub struct Network {
+    cpus: Vec<Cpu>,
+    links: Vec<(usize, usize)>,
+    outputs: Vec<Vec<i64>>,
+    quantum: u64,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Network {
+            cpus: vec![],
+            links: vec![],
+            outputs: vec![],
+            quantum: 100,
+        }
+    }
+}
+
+impl Network {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_quantum(&mut self, quantum: u64) {
+        self.quantum = quantum.max(1);
+    }
+
+    pub fn add(&mut self, cpu: Cpu) -> usize {
+        self.cpus.push(cpu);
+        self.outputs.push(vec![]);
+        self.cpus.len() - 1
+    }
+
+    // Send everything `from` outputs to `to`.  An output can go to several
+    // machines; one that goes nowhere is kept in `outputs`.
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.links.push((from, to));
+    }
+
+    pub fn cpu(&self, id: usize) -> &Cpu {
+        &self.cpus[id]
+    }
+
+    pub fn cpu_mut(&mut self, id: usize) -> &mut Cpu {
+        &mut self.cpus[id]
+    }
+
+    pub fn outputs(&self, id: usize) -> &[i64] {
+        &self.outputs[id]
+    }
+
+    fn deliver(&mut self, from: usize, values: Vec<i64>) {
+        let mut linked = false;
+        for &(_, to) in self.links.iter().filter(|&&(f, _)| f == from) {
+            linked = true;
+            for &v in &values {
+                self.cpus[to].send(v);
+            }
+        }
+        if !linked {
+            self.outputs[from].extend(values);
+        }
+    }
+
+    pub fn run(&mut self, fuel: &mut u64) -> NetworkStop {
+        loop {
+            for id in 0..self.cpus.len() {
+                let mut slice = self.quantum.min(*fuel);
+                let given = slice;
+                let mut sent = vec![];
+                let stop = loop {
+                    match self.cpus[id].run_with_fuel(&mut slice) {
+                        Stop::Output(v) => sent.push(v),
+                        stop => break stop,
+                    }
+                };
+                *fuel -= given - slice;
+                self.deliver(id, sent);
+                if let Stop::Fault(fault) = stop {
+                    return NetworkStop::Fault(id, fault);
+                }
+            }
+
+            let live = (0..self.cpus.len())
+                .filter(|&id| !self.cpus[id].halted())
+                .collect::<Vec<_>>();
+            if live.is_empty() {
+                return NetworkStop::Halted;
+            }
+            if live.iter().all(|&id| self.cpus[id].waiting()) {
+                return NetworkStop::Deadlock(live);
+            }
+            if *fuel == 0 {
+                return NetworkStop::OutOfFuel;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_network_pipeline() {
+    // Count down from 5 into a machine that keeps a running total, then echoes
+    let mut net = Network::new();
+    let producer = net.add(Cpu::new(super::parse("cpy 5 a\nout a\ndec a\njnz a -2")));
+    let adder = net.add(Cpu::new(super::parse(
+        "rcv b\ninc a\ndec b\njnz b -2\nout a\njnz 1 -5",
+    )));
+    net.connect(producer, adder);
+    net.set_quantum(3);
+    assert_eq!(net.run(&mut 10_000), NetworkStop::Deadlock(vec![adder]));
+    assert_eq!(net.cpu(adder).get('a'), Ok(15));
+    assert_eq!(net.outputs(adder), [5, 9, 12, 14, 15]);
+    assert!(net.outputs(producer).is_empty());
+}
+
+#[test]
+fn test_network_deadlock() {
+    let mut net = Network::new();
+    let a = net.add(Cpu::new(super::parse("out 1\nrcv a\nrcv a")));
+    let b = net.add(Cpu::new(super::parse("rcv a\nout a\nrcv b")));
+    net.connect(a, b);
+    net.connect(b, a);
+    assert_eq!(net.run(&mut 1000), NetworkStop::Deadlock(vec![a, b]));
+    assert_eq!(net.cpu(a).pc(), 2);
+
+    let mut net = Network::new();
+    net.add(Cpu::new(super::parse("jnz 1 0")));
+    assert_eq!(net.run(&mut 1000), NetworkStop::OutOfFuel);
+
+    let mut net = Network::new();
+    net.add(Cpu::new(super::parse("out 1")));
+    net.add(Cpu::new(super::parse("jnz 1 -5")));
+    assert_eq!(
+        net.run(&mut 1000),
+        NetworkStop::Fault(1, Fault::NegativePc(-5))
+    );
+    assert_eq!(net.outputs(0), [1]);
+}
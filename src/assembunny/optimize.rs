@@ -0,0 +1,114 @@
+use super::{Instruction, Register, Value};
+
+// Peephole patterns for the loops the puzzle programs spend all their time in.
+// Each fused op is anchored at the first instruction of its pattern, so a jump
+// into the middle of a loop still runs the plain instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fused {
+    // inc dest / dec counter / jnz counter -2 (in either order)
+    Add {
+        dest: Register,
+        counter: Register,
+    },
+    // cpy src scratch / <add dest scratch> / dec counter / jnz counter -5
+    Mul {
+        dest: Register,
+        src: Value,
+        scratch: Register,
+        counter: Register,
+    },
+}
+
+impl Fused {
+    pub fn opcode(&self) -> &'static str {
+        match self {
+            Fused::Add { .. } => "add",
+            Fused::Mul { .. } => "mul",
+        }
+    }
+
+    // Number of instructions the pattern covers
+    pub fn len(&self) -> i32 {
+        match self {
+            Fused::Add { .. } => 3,
+            Fused::Mul { .. } => 6,
+        }
+    }
+}
+
+pub fn optimize(program: &[Instruction]) -> Vec<Option<Fused>> {
+    (0..program.len())
+        .map(|pc| fuse_mul(&program[pc..]).or_else(|| fuse_add(&program[pc..])))
+        .collect()
+}
+
+fn fuse_add(window: &[Instruction]) -> Option<Fused> {
+    use Instruction::*;
+    use Value::*;
+    match window {
+        [Inc(Register(dest)), Dec(Register(counter)), Jnz(Register(test), Literal(-2)), ..]
+        | [Dec(Register(counter)), Inc(Register(dest)), Jnz(Register(test), Literal(-2)), ..]
+            if test == counter && dest != counter =>
+        {
+            Some(Fused::Add {
+                dest: *dest,
+                counter: *counter,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn fuse_mul(window: &[Instruction]) -> Option<Fused> {
+    use Instruction::*;
+    use Value::*;
+    match window {
+        [Cpy(src, Register(scratch)), _, _, _, Dec(Register(counter)), Jnz(Register(test), Literal(-5)), ..]
+            if test == counter && scratch != counter =>
+        {
+            match fuse_add(&window[1..]) {
+                Some(Fused::Add {
+                    dest,
+                    counter: inner,
+                }) if inner == *scratch
+                    && dest != *counter
+                    && ![dest, *scratch, *counter]
+                        .iter()
+                        .any(|&r| *src == Register(r)) =>
+                {
+                    Some(Fused::Mul {
+                        dest,
+                        src: *src,
+                        scratch: *scratch,
+                        counter: *counter,
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn test_optimize_day23() {
+    let program = super::parse(include_str!("../../input/2016/day23.txt"));
+    let fused = optimize(&program);
+    assert_eq!(
+        fused[4],
+        Some(Fused::Mul {
+            dest: 'a',
+            src: Value::Register('b'),
+            scratch: 'c',
+            counter: 'd'
+        })
+    );
+    assert_eq!(
+        fused[13],
+        Some(Fused::Add {
+            dest: 'c',
+            counter: 'd'
+        })
+    );
+    assert_eq!(fused.iter().flatten().count(), 3);
+}
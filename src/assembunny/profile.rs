@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+
+use super::Instruction;
+
+// Execution counts gathered by a Cpu with profiling enabled
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub executed: Vec<u64>,
+    pub not_taken: Vec<u64>,
+    pub toggled: Vec<u64>,
+    pub opcodes: BTreeMap<&'static str, u64>,
+    // Taken jumps, keyed by (from, to)
+    pub jumps: BTreeMap<(i32, i32), u64>,
+}
+
+// A backward jump that was taken, and the code it keeps running
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Loop {
+    pub start: i32,
+    pub end: i32,
+    pub iterations: u64,
+    pub ticks: u64,
+}
+
+impl Profile {
+    pub(super) fn new(len: usize) -> Self {
+        Self {
+            executed: vec![0; len],
+            not_taken: vec![0; len],
+            toggled: vec![0; len],
+            ..Default::default()
+        }
+    }
+
+    // Follow a program growing or shrinking
+    pub(super) fn resize(&mut self, len: usize) {
+        for counts in [&mut self.executed, &mut self.not_taken, &mut self.toggled] {
+            counts.resize(len, 0);
+        }
+        self.jumps.retain(|&(from, _), _| (from as usize) < len);
+    }
+
+    pub(super) fn record(&mut self, pc: i32, opcode: &'static str) {
+        self.executed[pc as usize] += 1;
+        *self.opcodes.entry(opcode).or_default() += 1;
+    }
+
+    pub(super) fn jump(&mut self, from: i32, to: Option<i32>) {
+        match to {
+            Some(to) => *self.jumps.entry((from, to)).or_default() += 1,
+            None => self.not_taken[from as usize] += 1,
+        }
+    }
+
+    pub(super) fn toggle(&mut self, addr: usize) {
+        self.toggled[addr] += 1;
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.executed.iter().sum()
+    }
+
+    pub fn taken(&self, pc: i32) -> u64 {
+        self.jumps
+            .iter()
+            .filter(|((from, _), _)| *from == pc)
+            .map(|(_, n)| n)
+            .sum()
+    }
+
+    // Loops by the number of ticks spent inside them, hottest first
+    pub fn hot_loops(&self) -> Vec<Loop> {
+        self.jumps
+            .iter()
+            .filter(|((from, to), _)| to <= from && *to >= 0)
+            .map(|(&(end, start), &iterations)| Loop {
+                start,
+                end,
+                iterations,
+                ticks: self
+                    .executed
+                    .iter()
+                    .take(end as usize + 1)
+                    .skip(start as usize)
+                    .sum(),
+            })
+            .sorted_by(|a, b| Ord::cmp(&b.ticks, &a.ticks).then(Ord::cmp(&a.start, &b.start)))
+            .collect()
+    }
+
+    pub fn report(&self, program: &[Instruction]) -> String {
+        let mut out = format!("{} ticks\n\n pc  executed  instruction\n", self.ticks());
+        for (pc, instruction) in program.iter().enumerate() {
+            out += &format!("{pc:>3}  {:>8}  {instruction:<12}", self.executed[pc]);
+            let taken = self.taken(pc as i32);
+            if matches!(instruction, Instruction::Jnz(..)) || taken + self.not_taken[pc] > 0 {
+                out += &format!("  taken {taken} / not {}", self.not_taken[pc]);
+            }
+            if self.toggled[pc] > 0 {
+                out += &format!("  toggled {}", self.toggled[pc]);
+            }
+            out = out.trim_end().to_string() + "\n";
+        }
+        out += "\nopcode  executed\n";
+        for (opcode, n) in &self.opcodes {
+            out += &format!("{opcode:<6}  {n:>8}\n");
+        }
+        out += "\nloop      iterations  ticks\n";
+        for l in self.hot_loops() {
+            let range = format!("{}..={}", l.start, l.end);
+            out += &format!("{range:<8}  {:>10}  {}\n", l.iterations, l.ticks);
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let pcs = (0..self.executed.len())
+            .map(|pc| {
+                format!(
+                    r#"{{"pc":{pc},"executed":{},"taken":{},"not_taken":{},"toggled":{}}}"#,
+                    self.executed[pc],
+                    self.taken(pc as i32),
+                    self.not_taken[pc],
+                    self.toggled[pc]
+                )
+            })
+            .join(",");
+        let opcodes = self
+            .opcodes
+            .iter()
+            .map(|(op, n)| format!(r#""{op}":{n}"#))
+            .join(",");
+        let loops = self
+            .hot_loops()
+            .iter()
+            .map(|l| {
+                format!(
+                    r#"{{"start":{},"end":{},"iterations":{},"ticks":{}}}"#,
+                    l.start, l.end, l.iterations, l.ticks
+                )
+            })
+            .join(",");
+        format!(
+            r#"{{"ticks":{},"pcs":[{pcs}],"opcodes":{{{opcodes}}},"loops":[{loops}]}}"#,
+            self.ticks()
+        )
+    }
+}
@@ -0,0 +1,239 @@
+use itertools::Itertools;
+use thiserror::Error;
+
+use super::{Backend, Cpu, Dialect, Instruction, Program, ProgramParseError, Register};
+
+// Everything needed to carry on a run later: the program is included as `tgl`
+// may have rewritten it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub registers: Vec<(Register, i64)>,
+    pub pc: i32,
+    pub program: Vec<Instruction>,
+    pub output: Option<i64>,
+    // Sent but not yet received
+    pub input: Vec<i64>,
+}
+
+impl Cpu {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            registers: self.registers().collect(),
+            pc: self.pc,
+            program: self.program.clone(),
+            output: self.output,
+            input: self.inbox.iter().copied().collect(),
+        }
+    }
+
+    // Pick up from a snapshot, keeping this Cpu's arithmetic, optimization
+    // and profile.  Any fault and recorded history are forgotten.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        (self.names, self.registers) = snapshot.registers.iter().copied().unzip();
+        self.pc = snapshot.pc;
+        self.program = snapshot.program.clone();
+        self.output = snapshot.output;
+        self.inbox = snapshot.input.iter().copied().collect();
+        self.fault = None;
+        if let Some(profile) = &mut self.profile {
+            profile.resize(self.program.len());
+        }
+        self.restart_history();
+        if self.optimized {
+            self.optimize();
+        }
+        if self.ops.is_some() {
+            self.set_backend(Backend::Compiled);
+        }
+    }
+
+    // An independent copy to explore from, leaving this one where it is
+    pub fn fork(&self) -> Cpu {
+        self.clone()
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SnapshotParseError {
+    #[error("missing `{0}` line")]
+    Missing(&'static str),
+
+    #[error("bad `{0}` line `{1}`")]
+    Bad(&'static str, String),
+
+    #[error("in program:\n{0}")]
+    Program(#[from] ProgramParseError),
+}
+
+// The text form is line based, with the program last:
+//
+//   registers a=1 b=0 c=7 d=0
+//   pc 3
+//   output -
+//   input 4 5
+//   program
+//   cpy a b
+//   ...
+//
+impl std::fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "registers {}",
+            self.registers
+                .iter()
+                .map(|(r, v)| format!("{r}={v}"))
+                .join(" ")
+        )?;
+        writeln!(f, "pc {}", self.pc)?;
+        match self.output {
+            Some(v) => writeln!(f, "output {v}")?,
+            None => writeln!(f, "output -")?,
+        }
+        write!(f, "input")?;
+        for v in &self.input {
+            write!(f, " {v}")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "program")?;
+        for instruction in &self.program {
+            writeln!(f, "{instruction}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Snapshot {
+    type Err = SnapshotParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        let mut field = |name: &'static str| {
+            let line = lines.next().ok_or(SnapshotParseError::Missing(name))?;
+            match line.split_once(' ') {
+                Some((key, rest)) if key == name => Ok(rest),
+                None if line == name => Ok(""),
+                _ => Err(SnapshotParseError::Missing(name)),
+            }
+        };
+
+        let text = field("registers")?;
+        let bad = |name| SnapshotParseError::Bad(name, text.to_string());
+        let registers = text
+            .split_ascii_whitespace()
+            .map(|tok| {
+                let (r, v) = tok.split_once('=').ok_or_else(|| bad("registers"))?;
+                match r.chars().collect_vec()[..] {
+                    [r] => Ok((r, v.parse().map_err(|_| bad("registers"))?)),
+                    _ => Err(bad("registers")),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let text = field("pc")?;
+        let pc = text
+            .parse()
+            .map_err(|_| SnapshotParseError::Bad("pc", text.to_string()))?;
+
+        let text = field("output")?;
+        let output = match text {
+            "-" => None,
+            v => Some(
+                v.parse()
+                    .map_err(|_| SnapshotParseError::Bad("output", text.to_string()))?,
+            ),
+        };
+
+        let text = field("input")?;
+        let input = text
+            .split_ascii_whitespace()
+            .map(|v| v.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| SnapshotParseError::Bad("input", text.to_string()))?;
+
+        field("program")?;
+        let dialect = Dialect {
+            registers: registers.iter().map(|&(r, _)| r).collect(),
+            ..Dialect::extended()
+        };
+        let program = Program::parse_with(&lines.join("\n"), &dialect)?;
+
+        Ok(Snapshot {
+            registers,
+            pc,
+            program,
+            output,
+            input,
+        })
+    }
+}
+
+#[test]
+fn test_snapshot_restore() {
+    let mut cpu = super::day23();
+    cpu.run_with_fuel(&mut 20_000);
+    let snapshot = cpu.snapshot();
+    assert_ne!(snapshot.program, super::day23().program);
+
+    cpu.run();
+    let mut resumed = super::day23();
+    resumed.restore(&snapshot);
+    resumed.run();
+    assert_eq!(resumed.get('a'), Ok(11120));
+    assert_eq!(resumed.snapshot(), cpu.snapshot());
+
+    // The profile grows to fit a longer program
+    let mut short = Cpu::new(Program::parse("inc a").unwrap());
+    short.enable_profiling();
+    short.restore(&snapshot);
+    short.run();
+    assert_eq!(short.get('a'), Ok(11120));
+    assert!(short
+        .profile()
+        .unwrap()
+        .report(short.program())
+        .contains("tgl"));
+
+    // And shrinks to fit a shorter one, forgetting jumps from past its end
+    let tiny = Cpu::new(Program::parse("inc a").unwrap()).snapshot();
+    short.restore(&tiny);
+    assert_eq!(short.profile().unwrap().hot_loops(), vec![]);
+    short.run();
+    assert_eq!(short.profile().unwrap().ticks(), 1);
+}
+
+#[test]
+fn test_snapshot_text() {
+    let mut cpu = Cpu::new(Program::parse("cpy 3 a\nout a\ntgl a").unwrap());
+    cpu.run_till_output();
+    let snapshot = cpu.snapshot();
+    let text = snapshot.to_string();
+    assert_eq!(
+        text,
+        "registers a=3 b=0 c=0 d=0\npc 2\noutput 3\ninput\nprogram\ncpy 3 a\nout a\ntgl a\n"
+    );
+    assert_eq!(text.parse(), Ok(snapshot));
+
+    assert_eq!(
+        "registers a=1\npc x".parse::<Snapshot>(),
+        Err(SnapshotParseError::Bad("pc", "x".into()))
+    );
+    assert_eq!(
+        "registers x=1\npc 0\noutput -\ninput\nprogram\ninc a"
+            .parse::<Snapshot>()
+            .unwrap_err()
+            .to_string(),
+        "in program:\nline 1: unknown register `a`"
+    );
+}
+
+#[test]
+fn test_fork() {
+    // Both forks share the run up to the first output
+    let mut cpu = Cpu::new(Program::parse("out a\ninc a\nout a").unwrap());
+    cpu.run_till_output();
+    let mut fork = cpu.fork();
+    fork.set('a', 10).unwrap();
+    assert_eq!(fork.run_till_output(), Some(11));
+    assert_eq!(cpu.run_till_output(), Some(1));
+}
@@ -0,0 +1,440 @@
+// Run solutions without cargo-aoc, picking the day, part, variant and input.
+//
+//   cargo run --release --bin advent -- 19 1 slice
+//   cargo run --release --bin advent -- --json --input - 12 < input/2016/day12.txt
+//   cargo run --release --bin advent -- --verify
+//   cargo run --release --bin advent -- --all --quick
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use advent_2016::registry::{self, Variant};
+
+const USAGE: &str = "\
+usage: advent [options] <day> [part] [variant]
+       advent --list [day]
+       advent --verify [day]
+       advent --all [--jobs <n>] [--quick]
+
+  --input <path>   read the puzzle input from here, or stdin for `-`
+                   (default input/2016/day<day>.txt, and --verify then
+                   needs a day)
+  --json           print results as JSON
+  --list           list the solutions and their variants
+  --verify         check every solution against the known answers
+  --all            run every day at once, with a table of answers and timings
+  --jobs <n>       how many to run at a time (default one per CPU)
+  --quick          leave out the slow solutions
+  --answers <path> where the known answers are
+                   (default input/2016/answers.txt)";
+
+struct Args {
+    list: bool,
+    verify: bool,
+    all: bool,
+    quick: bool,
+    jobs: Option<usize>,
+    json: bool,
+    input: Option<String>,
+    answers: String,
+    day: Option<u32>,
+    part: Option<u32>,
+    variant: Option<String>,
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    std::process::exit(2);
+}
+
+fn parse_args() -> Args {
+    let mut args = Args {
+        list: false,
+        verify: false,
+        all: false,
+        quick: false,
+        jobs: None,
+        json: false,
+        input: None,
+        answers: "input/2016/answers.txt".to_string(),
+        day: None,
+        part: None,
+        variant: None,
+    };
+    let mut positional = vec![];
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "--list" => args.list = true,
+            "--verify" => args.verify = true,
+            "--all" => args.all = true,
+            "--quick" => args.quick = true,
+            "--jobs" => {
+                let jobs = argv.next().and_then(|n| n.parse().ok());
+                args.jobs = Some(jobs.filter(|&n| n > 0).unwrap_or_else(|| usage()));
+            }
+            "--json" => args.json = true,
+            "--input" => args.input = Some(argv.next().unwrap_or_else(|| usage())),
+            "--answers" => args.answers = argv.next().unwrap_or_else(|| usage()),
+            "-h" | "--help" => usage(),
+            _ if arg.starts_with("--") => usage(),
+            _ => positional.push(arg),
+        }
+    }
+    let number = |s: &String| {
+        s.trim_start_matches("day")
+            .trim_start_matches("part")
+            .parse()
+            .unwrap_or_else(|_| usage())
+    };
+    match &positional[..] {
+        [] => (),
+        [day] => args.day = Some(number(day)),
+        [day, part] => (args.day, args.part) = (Some(number(day)), Some(number(part))),
+        [day, part, variant] => {
+            (args.day, args.part) = (Some(number(day)), Some(number(part)));
+            args.variant = Some(variant.clone());
+        }
+        _ => usage(),
+    }
+    args
+}
+
+fn read_input(path: &str) -> String {
+    let mut input = String::new();
+    let read = match path {
+        "-" => std::io::stdin().read_to_string(&mut input).map(|_| ()),
+        _ => std::fs::read_to_string(path).map(|s| input = s),
+    };
+    read.unwrap_or_else(|e| {
+        eprintln!("{path}: {e}");
+        std::process::exit(1);
+    });
+    input
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            c if c.is_control() => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out + "\""
+}
+
+fn list(day: Option<u32>) {
+    for variant in registry::variants() {
+        if day.is_none_or(|day| day == variant.day) {
+            println!("{variant}");
+        }
+    }
+}
+
+// Run everything with a known answer, one line per solution, and fail if any
+// of them got it wrong
+fn verify(args: &Args) {
+    let answers = registry::parse_answers(&read_input(&args.answers));
+    let (mut right, mut wrong, mut unchecked) = (0, 0, 0);
+    let mut results = vec![];
+    let mut inputs = std::collections::HashMap::new();
+    for variant in registry::variants() {
+        if args.day.is_some_and(|day| day != variant.day)
+            || args.part.is_some_and(|part| part != variant.part)
+        {
+            continue;
+        }
+        let name = variant.to_string();
+        let Some(expected) = answers.get(&name) else {
+            unchecked += 1;
+            if !args.json {
+                println!("{name:<24} skipped, no answer recorded");
+            }
+            continue;
+        };
+        let input = inputs.entry(variant.day).or_insert_with(|| {
+            let path = args.input.clone();
+            read_input(&path.unwrap_or_else(|| format!("input/2016/day{}.txt", variant.day)))
+        });
+
+        let start = Instant::now();
+        // A solver that panics is as wrong as one that gets it wrong, and
+        // shouldn't stop the rest being checked
+        let result = std::panic::catch_unwind(|| variant.run(input))
+            .unwrap_or_else(|_| Err("panicked".into()))
+            .map_err(|e| e.to_string());
+        let millis = start.elapsed().as_secs_f64() * 1000.0;
+        let status = match &result {
+            Ok(answer) if answer == expected => "ok".to_string(),
+            Ok(answer) => format!(
+                "WRONG, got {} but expected {}",
+                registry::escape(answer),
+                registry::escape(expected)
+            ),
+            Err(error) => format!("FAILED: {error}"),
+        };
+        match status.as_str() {
+            "ok" => right += 1,
+            _ => wrong += 1,
+        }
+        if args.json {
+            results.push(format!(
+                r#"{{"day":{},"part":{},"variant":{},"ok":{},"millis":{millis:.3}}}"#,
+                variant.day,
+                variant.part,
+                variant.name.map_or("null".to_string(), json_string),
+                status == "ok"
+            ));
+        } else {
+            println!("{name:<24} {millis:>10.1}ms  {status}");
+        }
+    }
+    if args.json {
+        println!("[{}]", results.join(","));
+    } else {
+        println!("\n{right} right, {wrong} wrong, {unchecked} unchecked");
+    }
+    if wrong > 0 {
+        std::process::exit(1);
+    }
+}
+
+struct Timing {
+    variant: &'static Variant,
+    result: Result<String, String>,
+    parse: Duration,
+    solve: Duration,
+}
+
+impl Timing {
+    fn failed(variant: &'static Variant, error: String) -> Self {
+        Timing {
+            variant,
+            result: Err(error),
+            parse: Duration::ZERO,
+            solve: Duration::ZERO,
+        }
+    }
+}
+
+fn time(variant: &'static Variant, input: &str) -> Timing {
+    let start = Instant::now();
+    let parsed = variant.parse(input);
+    let parse = start.elapsed();
+    let start = Instant::now();
+    let result = parsed.map(|parsed| variant.solve(&parsed));
+    Timing {
+        variant,
+        result: result.map_err(|e| e.to_string()),
+        parse,
+        solve: start.elapsed(),
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+// Every day's main solutions on a pool of worker threads, slow ones first so
+// they aren't left until last, then a table of how each went
+fn run_all(args: &Args) {
+    let chosen = (1..=25)
+        .flat_map(|day| [1, 2].map(|part| registry::defaults(day, part)))
+        .flatten()
+        .filter(|v| !(args.quick && v.slow()))
+        .collect::<Vec<_>>();
+    let inputs = (1..=25)
+        .map(|day| {
+            let path = format!("input/2016/day{day}.txt");
+            std::fs::read_to_string(&path).map_err(|e| format!("{path}: {e}"))
+        })
+        .collect::<Vec<_>>();
+    let answers = std::fs::read_to_string(&args.answers)
+        .map(|text| registry::parse_answers(&text))
+        .unwrap_or_default();
+
+    let mut queue = (0..chosen.len()).collect::<Vec<_>>();
+    queue.sort_by_key(|&i| !chosen[i].slow());
+    let jobs = args
+        .jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let next = AtomicUsize::new(0);
+    let timings = Mutex::new((0..chosen.len()).map(|_| None).collect::<Vec<_>>());
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(chosen.len()) {
+            scope.spawn(|| {
+                while let Some(&i) = queue.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let variant = chosen[i];
+                    let timing = match &inputs[variant.day as usize - 1] {
+                        Ok(input) => std::panic::catch_unwind(|| time(variant, input))
+                            .unwrap_or_else(|_| Timing::failed(variant, "panicked".to_string())),
+                        Err(error) => Timing::failed(variant, error.clone()),
+                    };
+                    timings.lock().unwrap()[i] = Some(timing);
+                }
+            });
+        }
+    });
+    let elapsed = start.elapsed();
+    let timings = timings.into_inner().unwrap().into_iter().flatten();
+
+    let (mut bad, mut unchecked) = (0, 0);
+    let mut rows = vec![];
+    let mut drawings = vec![];
+    if !args.json {
+        println!(
+            "{:<22} {:<20} {:>10} {:>11}",
+            "solution", "answer", "parse", "solve"
+        );
+    }
+    for timing in timings {
+        let variant = timing.variant;
+        let name = variant.to_string();
+        let checked = match (&timing.result, answers.get(&name)) {
+            (Ok(answer), Some(expected)) => Some(answer == expected),
+            _ => None,
+        };
+        if timing.result.is_err() || checked == Some(false) {
+            bad += 1;
+        } else if checked.is_none() {
+            unchecked += 1;
+        }
+        let (parse, solve) = (millis(timing.parse), millis(timing.solve));
+        if args.json {
+            let (key, value) = match &timing.result {
+                Ok(answer) => ("answer", answer),
+                Err(error) => ("error", error),
+            };
+            rows.push(format!(
+                r#"{{"day":{},"part":{},"variant":{},"{key}":{},"parse_millis":{parse:.3},"solve_millis":{solve:.3},"slow":{},"ok":{}}}"#,
+                variant.day,
+                variant.part,
+                variant.name.map_or("null".to_string(), json_string),
+                json_string(value),
+                variant.slow(),
+                checked.map_or("null".to_string(), |ok| ok.to_string()),
+            ));
+            continue;
+        }
+        let answer = match &timing.result {
+            Ok(answer) if answer.contains('\n') => {
+                drawings.push((name.clone(), answer.clone()));
+                "(below)".to_string()
+            }
+            Ok(answer) => answer.clone(),
+            Err(error) => format!("failed: {error}"),
+        };
+        let mut notes = vec![];
+        match checked {
+            Some(false) => notes.push("WRONG"),
+            None if timing.result.is_ok() => notes.push("unchecked"),
+            _ => (),
+        }
+        if variant.slow() {
+            notes.push("slow");
+        }
+        let row = format!(
+            "{name:<22} {answer:<20} {parse:>8.1}ms {solve:>9.1}ms  {}",
+            notes.join(", ")
+        );
+        println!("{}", row.trim_end());
+    }
+    if args.json {
+        println!("[{}]", rows.join(","));
+    } else {
+        for (name, drawing) in drawings {
+            println!("\n{name}:\n{}", drawing.trim_matches('\n'));
+        }
+        println!(
+            "\n{} solutions in {:.1}s, {jobs} at a time, {bad} wrong or failed, {unchecked} unchecked",
+            chosen.len(),
+            elapsed.as_secs_f64()
+        );
+    }
+    if bad > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn main() {
+    let args = parse_args();
+    if args.list {
+        return list(args.day);
+    }
+    if args.verify {
+        // One input can only be checked against one day's answers
+        if args.input.is_some() && args.day.is_none() {
+            usage();
+        }
+        return verify(&args);
+    }
+    if args.all {
+        if args.input.is_some() || args.day.is_some() {
+            usage();
+        }
+        return run_all(&args);
+    }
+    let Some(day) = args.day else { usage() };
+
+    let chosen: Vec<&Variant> = match (args.part, &args.variant) {
+        (Some(part), Some(name)) => match registry::find(day, part, name) {
+            Some(variant) => vec![variant],
+            None => {
+                eprintln!("no variant `{name}` of day {day} part {part}; try --list {day}");
+                std::process::exit(1);
+            }
+        },
+        (Some(part), None) => registry::defaults(day, part),
+        (None, _) => [1, 2]
+            .into_iter()
+            .flat_map(|part| registry::defaults(day, part))
+            .collect(),
+    };
+    if chosen.is_empty() {
+        eprintln!("nothing registered for that; try --list");
+        std::process::exit(1);
+    }
+
+    let path = args
+        .input
+        .unwrap_or_else(|| format!("input/2016/day{day}.txt"));
+    let input = read_input(&path);
+
+    let mut failed = false;
+    let mut results = vec![];
+    for variant in chosen {
+        let result = variant.run(&input).map_err(|e| e.to_string());
+        failed |= result.is_err();
+        if args.json {
+            let name = variant.name.map_or("null".to_string(), json_string);
+            let (key, value) = match &result {
+                Ok(answer) => ("answer", answer),
+                Err(error) => ("error", error),
+            };
+            results.push(format!(
+                r#"{{"day":{},"part":{},"variant":{name},"{key}":{}}}"#,
+                variant.day,
+                variant.part,
+                json_string(value)
+            ));
+        } else {
+            match result {
+                Ok(answer) if answer.contains('\n') => println!("{variant}:\n{answer}"),
+                Ok(answer) => println!("{variant}: {answer}"),
+                Err(error) => println!("{variant}: failed: {error}"),
+            }
+        }
+    }
+    if args.json {
+        println!("[{}]", results.join(","));
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
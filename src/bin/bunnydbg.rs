@@ -0,0 +1,206 @@
+// A small line-oriented debugger for assembunny programs, in either the
+// puzzle format or labelled assembler source, and the extended dialect.
+//
+//   cargo run --bin bunnydbg -- input/2016/day23.txt a=7
+use std::io::{BufRead, Write};
+
+use advent_2016::assembunny::{
+    assemble_with, decompile, lint_source, Cpu, Debugger, Dialect, Event,
+};
+
+const HELP: &str = "\
+s [n]      step n ticks (default 1)
+c          continue to a breakpoint, watchpoint or halt
+o          run to the next output
+b <pc>     set a breakpoint
+d <pc>     delete a breakpoint
+w <reg>    watch a register
+u <reg>    unwatch a register
+set <reg> <value>
+send <value>  give a value to `rcv`
+r          show registers
+p [json]   show the execution profile
+l [n]      list n instructions either side of pc (default 5)
+x          decompile the program as it now stands
+rec        start recording, so ticks can be undone
+bs [n]     step back n recorded ticks (default 1)
+rw <tick>  rewind to a recorded tick
+lc <reg>   find the tick that last changed a register
+lt <pc>    find the tick that last toggled an instruction
+q          quit";
+
+fn report(events: &[Event]) {
+    for event in events {
+        println!("  {event}");
+    }
+}
+
+fn list(dbg: &Debugger, radius: usize) {
+    for line in dbg.listing(radius) {
+        println!("{line}");
+    }
+}
+
+fn registers(dbg: &Debugger) {
+    let cpu = dbg.cpu();
+    print!("  pc={}", cpu.pc());
+    for (r, v) in cpu.registers() {
+        print!(" {r}={v}");
+    }
+    if cpu.recording() {
+        print!("  (tick {})", cpu.history().len());
+    }
+    println!();
+}
+
+fn register(arg: &str) -> Option<char> {
+    match arg.chars().collect::<Vec<_>>()[..] {
+        [r] => Some(r),
+        _ => None,
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        eprintln!("usage: bunnydbg <program> [reg=value...]");
+        std::process::exit(2);
+    };
+    let source = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        eprintln!("{path}: {e}");
+        std::process::exit(1);
+    });
+    let dialect = Dialect::extended();
+    let program = assemble_with(&source, &dialect).unwrap_or_else(|e| {
+        eprintln!("{path}:\n{e}");
+        std::process::exit(1);
+    });
+
+    for warning in lint_source(&source, &dialect).unwrap_or_default() {
+        eprintln!("{path}: {warning}");
+    }
+
+    let mut cpu = Cpu::new(program);
+    cpu.enable_profiling();
+    let mut dbg = Debugger::new(cpu);
+    for arg in args {
+        match arg.split_once('=').map(|(r, v)| (register(r), v.parse())) {
+            Some((Some(r), Ok(v))) => {
+                if let Err(e) = dbg.cpu_mut().set(r, v) {
+                    eprintln!("ignoring `{arg}`: {e}");
+                }
+            }
+            _ => eprintln!("ignoring `{arg}`, expected reg=value"),
+        }
+    }
+
+    list(&dbg, 5);
+    let stdin = std::io::stdin();
+    loop {
+        print!("(bunnydbg) ");
+        std::io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+        let toks = line.split_ascii_whitespace().collect::<Vec<_>>();
+        match toks[..] {
+            [] => continue,
+            ["s"] | ["s", _] => {
+                let n = toks.get(1).and_then(|n| n.parse().ok()).unwrap_or(1);
+                for _ in 0..n {
+                    let events = dbg.step();
+                    report(&events);
+                    if events.iter().any(Event::stops) {
+                        break;
+                    }
+                }
+                list(&dbg, 0);
+            }
+            ["c"] => {
+                report(&dbg.cont());
+                list(&dbg, 0);
+            }
+            ["o"] => {
+                report(&dbg.next_output());
+                list(&dbg, 0);
+            }
+            ["b", pc] | ["d", pc] => match pc.parse() {
+                Ok(pc) if toks[0] == "b" => dbg.set_breakpoint(pc),
+                Ok(pc) => {
+                    if !dbg.clear_breakpoint(pc) {
+                        println!("  no breakpoint at {pc}");
+                    }
+                }
+                Err(_) => println!("  bad address `{pc}`"),
+            },
+            ["w", r] | ["u", r] => match register(r) {
+                Some(r) if toks[0] == "w" => {
+                    if let Err(e) = dbg.watch(r) {
+                        println!("  {e}");
+                    }
+                }
+                Some(r) => {
+                    dbg.unwatch(r);
+                }
+                None => println!("  unknown register `{r}`"),
+            },
+            ["set", r, v] => match (register(r), v.parse()) {
+                (Some(r), Ok(v)) => {
+                    if let Err(e) = dbg.cpu_mut().set(r, v) {
+                        println!("  {e}");
+                    }
+                }
+                _ => println!("  usage: set <reg> <value>"),
+            },
+            ["send", v] => match v.parse() {
+                Ok(v) => dbg.cpu_mut().send(v),
+                Err(_) => println!("  usage: send <value>"),
+            },
+            ["r"] => registers(&dbg),
+            ["p"] => {
+                let cpu = dbg.cpu();
+                print!("{}", cpu.profile().unwrap().report(cpu.program()))
+            }
+            ["p", "json"] => println!("{}", dbg.cpu().profile().unwrap().to_json()),
+            ["l"] | ["l", _] => {
+                let n = toks.get(1).and_then(|n| n.parse().ok()).unwrap_or(5);
+                list(&dbg, n);
+            }
+            ["x"] => print!("{}", decompile(dbg.cpu().program())),
+            ["rec"] => dbg.cpu_mut().enable_recording(),
+            ["bs"] | ["bs", _] => {
+                let n = toks.get(1).and_then(|n| n.parse().ok()).unwrap_or(1);
+                for _ in 0..n {
+                    if !dbg.cpu_mut().step_back() {
+                        println!("  nothing recorded to step back over");
+                        break;
+                    }
+                }
+                list(&dbg, 0);
+            }
+            ["rw", tick] => match tick.parse() {
+                Ok(tick) if dbg.cpu_mut().rewind_to(tick) => list(&dbg, 0),
+                Ok(tick) => println!("  tick {tick} hasn't been recorded"),
+                Err(_) => println!("  bad tick `{tick}`"),
+            },
+            ["lc", r] => match register(r).map(|r| dbg.cpu().last_change(r)) {
+                Some(Some(tick)) => {
+                    println!("  tick {tick}, at pc {}", dbg.cpu().history()[tick].pc)
+                }
+                Some(None) => println!("  no recorded change to {r}"),
+                None => println!("  unknown register `{r}`"),
+            },
+            ["lt", pc] => match pc.parse().map(|pc| dbg.cpu().last_toggle(pc)) {
+                Ok(Some(tick)) => println!(
+                    "  tick {tick}, by the tgl at pc {}",
+                    dbg.cpu().history()[tick].pc
+                ),
+                Ok(None) => println!("  no recorded toggle of {pc}"),
+                Err(_) => println!("  bad address `{pc}`"),
+            },
+            ["q"] => break,
+            _ => println!("{HELP}"),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+// Write the control flow graph of an assembunny program as Graphviz source.
+//
+//   cargo run --bin bunnydot -- input/2016/day23.txt | dot -Tsvg > day23.svg
+use advent_2016::assembunny::{assemble, FlowGraph};
+
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: bunnydot <program>");
+        std::process::exit(2);
+    };
+    let source = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        eprintln!("{path}: {e}");
+        std::process::exit(1);
+    });
+    let program = assemble(&source).unwrap_or_else(|e| {
+        eprintln!("{path}:\n{e}");
+        std::process::exit(1);
+    });
+    print!("{}", FlowGraph::new(&program).to_dot());
+}
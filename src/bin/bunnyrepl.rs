@@ -0,0 +1,197 @@
+// A scratchpad for assembunny: type instructions to build up a program, or
+// load one, then run it, step it and patch it as you go.  The extended
+// dialect's `add`, `mul`, `nop` and `rcv` are all allowed.
+//
+//   cargo run --bin bunnyrepl -- input/2016/day12.txt
+use std::io::{BufRead, Write};
+
+use advent_2016::assembunny::{
+    assemble_with, disassemble, lint, Cpu, Dialect, Instruction, InstructionParseError, Stop,
+};
+
+const HELP: &str = "\
+<instruction>          add an instruction to the end of the program
+load <file>            replace the program and start again
+poke <addr> <instr>    replace the instruction at addr
+set <reg> <value>
+regs                   show pc and registers
+step [n]               run n ticks (default 1)
+run                    run until the program halts or waits
+out                    show everything output so far
+dis                    disassemble the program as it stands
+lint                   look for likely mistakes in the program
+reset                  back to pc 0 with clear registers, keeping any pokes
+quit";
+
+// Give up on a `run` after this many ticks, in case it never halts
+const FUEL: u64 = 100_000_000;
+
+struct Repl {
+    // The program as loaded, typed and poked, but not toggled
+    program: Vec<Instruction>,
+    cpu: Cpu,
+    outputs: Vec<i64>,
+}
+
+impl Repl {
+    fn new(program: Vec<Instruction>) -> Self {
+        Repl {
+            cpu: Cpu::new(program.clone()),
+            program,
+            outputs: vec![],
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Repl::new(std::mem::take(&mut self.program));
+    }
+
+    fn poke(&mut self, addr: usize, instruction: Instruction) {
+        if !self.cpu.poke(addr, instruction) {
+            println!("  {addr} is past the end of the program");
+            return;
+        }
+        match self.program.get_mut(addr) {
+            Some(slot) => *slot = instruction,
+            None => self.program.push(instruction),
+        }
+    }
+
+    // Run for up to `fuel` ticks, reporting outputs as they come
+    fn run(&mut self, mut fuel: u64) {
+        loop {
+            match self.cpu.run_with_fuel(&mut fuel) {
+                Stop::Output(v) => {
+                    println!("  out {v}");
+                    self.outputs.push(v);
+                }
+                Stop::OutOfFuel => return,
+                Stop::Halted => return println!("  halted"),
+                Stop::Blocked => return println!("  waiting on rcv"),
+                Stop::Fault(fault) => return println!("  fault: {fault:?}"),
+            }
+        }
+    }
+
+    fn registers(&self) {
+        print!("  pc={}", self.cpu.pc());
+        for (r, v) in self.cpu.registers() {
+            print!(" {r}={v}");
+        }
+        println!();
+    }
+
+    // Only instructions get an address, not a label past the end
+    fn disassemble(&self) {
+        let pc = self.cpu.pc();
+        let len = self.cpu.program().len();
+        for (addr, line) in disassemble(self.cpu.program()).lines().enumerate() {
+            let mark = if addr as i32 == pc { "=>" } else { "  " };
+            if addr < len {
+                println!("{mark}{addr:>4}  {line}");
+            } else {
+                println!("{mark}      {line}");
+            }
+        }
+    }
+}
+
+fn register(arg: &str) -> Option<char> {
+    match arg.chars().collect::<Vec<_>>()[..] {
+        [r] => Some(r),
+        _ => None,
+    }
+}
+
+fn parse(s: &str) -> Result<Instruction, InstructionParseError> {
+    Instruction::parse_with(s, &Dialect::extended())
+}
+
+fn load(path: &str) -> Option<Vec<Instruction>> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| eprintln!("{path}: {e}"))
+        .ok()?;
+    assemble_with(&source, &Dialect::extended())
+        .map_err(|e| eprintln!("{path}:\n{e}"))
+        .ok()
+}
+
+fn main() {
+    let program = match std::env::args().nth(1) {
+        Some(path) => load(&path).unwrap_or_else(|| std::process::exit(1)),
+        None => vec![],
+    };
+    let mut repl = Repl::new(program);
+
+    let stdin = std::io::stdin();
+    loop {
+        print!("(bunny) ");
+        std::io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+        let toks = line.split_ascii_whitespace().collect::<Vec<_>>();
+        match toks[..] {
+            [] => continue,
+            ["load", path] => {
+                if let Some(program) = load(path) {
+                    repl = Repl::new(program);
+                }
+            }
+            ["poke", addr, ..] => {
+                let instruction = toks[2..].join(" ");
+                match (addr.parse(), parse(&instruction)) {
+                    (Ok(addr), Ok(instruction)) => repl.poke(addr, instruction),
+                    (Err(_), _) => println!("  bad address `{addr}`"),
+                    (_, Err(e)) => println!("  {e}"),
+                }
+            }
+            ["set", r, v] => match (register(r), v.parse()) {
+                (Some(r), Ok(v)) => {
+                    if let Err(e) = repl.cpu.set(r, v) {
+                        println!("  {e}");
+                    }
+                }
+                _ => println!("  usage: set <reg> <value>"),
+            },
+            ["regs"] => repl.registers(),
+            ["step"] | ["step", _] => {
+                let n = toks.get(1).and_then(|n| n.parse().ok()).unwrap_or(1);
+                repl.run(n);
+                repl.registers();
+            }
+            ["run"] => {
+                repl.run(FUEL);
+                if !repl.cpu.halted() && !repl.cpu.waiting() {
+                    println!("  still running after {FUEL} ticks");
+                }
+                repl.registers();
+            }
+            ["out"] => println!(
+                "  {}",
+                repl.outputs
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            ["dis"] => repl.disassemble(),
+            ["lint"] => {
+                for warning in lint(repl.cpu.program()) {
+                    println!("  {}: {}", warning.pc, warning.lint);
+                }
+            }
+            ["reset"] => repl.reset(),
+            ["quit"] | ["q"] => break,
+            ["help"] => println!("{HELP}"),
+            _ => match parse(&line) {
+                Ok(instruction) => {
+                    let end = repl.program.len();
+                    repl.poke(end, instruction);
+                }
+                Err(e) => println!("  {e} (try `help`)"),
+            },
+        }
+    }
+}
@@ -2,24 +2,48 @@ use std::collections::HashSet;
 
 use ndarray::prelude::*;
 
+use crate::input::{parse_lines, InputError, Malformed};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+fn parse_step(step: &str) -> Result<(Turn, i32), Malformed> {
+    let turn = match step.chars().next() {
+        Some('L') => Turn::Left,
+        Some('R') => Turn::Right,
+        _ => return Err(Malformed::Expected("steps like R2 or L3")),
+    };
+    Ok((turn, step[1..].parse()?))
+}
+
+#[aoc_generator(day1)]
+fn generate(input: &str) -> Result<Vec<(Turn, i32)>, InputError> {
+    let lines = parse_lines(1, input, |line| {
+        line.split(", ")
+            .map(parse_step)
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    Ok(lines.concat())
+}
+
+fn turn(facing: usize, turn: Turn, directions: usize) -> usize {
+    match turn {
+        Turn::Left => (directions + facing - 1) % directions,
+        Turn::Right => (facing + 1) % directions,
+    }
+}
+
 #[aoc(day1, part1)]
-fn solve(input: &str) -> i32 {
+fn solve(steps: &[(Turn, i32)]) -> i32 {
     let mut position = array![0, 0];
-    let vectors = [
-        array![0_i32, 1],
-        array![1, 0],
-        array![0, -1],
-        array![-1, 0],
-    ];
+    let vectors = [array![0_i32, 1], array![1, 0], array![0, -1], array![-1, 0]];
     let mut facing = 0;
 
-    for cmd in input.split(", ") {
-        facing = match cmd.chars().next().unwrap() {
-            'L' => (vectors.len() + facing - 1) % vectors.len(),
-            'R' => (facing + 1) % vectors.len(),
-            _ => 0,
-        };
-        let distance = cmd[1..].parse::<i32>().unwrap();
+    for &(direction, distance) in steps {
+        facing = turn(facing, direction, vectors.len());
         let delta = &vectors[facing] * distance;
         position += &delta;
     }
@@ -28,36 +52,27 @@ fn solve(input: &str) -> i32 {
 
 #[test]
 fn test_solve() {
+    let solve = |input| solve(&generate(input).unwrap());
     assert_eq!(solve("R2, L3"), 5);
     assert_eq!(solve("R2, R2, R2"), 2);
     assert_eq!(solve("R5, L5, R5, R3"), 12);
 }
 
 #[aoc(day1, part2)]
-fn solve2(input: &str) -> i32 {
+fn solve2(steps: &[(Turn, i32)]) -> i32 {
     let mut position = array![0, 0];
-    let vectors = [
-        array![0_i32, 1],
-        array![1, 0],
-        array![0, -1],
-        array![-1, 0],
-    ];
+    let vectors = [array![0_i32, 1], array![1, 0], array![0, -1], array![-1, 0]];
     let mut facing = 0;
     let mut visited: HashSet<(i32, i32)> = HashSet::new();
     visited.insert((0, 0));
 
-    for cmd in input.split(", ") {
-        facing = match cmd.chars().next().unwrap() {
-            'L' => (vectors.len() + facing - 1) % vectors.len(),
-            'R' => (facing + 1) % vectors.len(),
-            _ => 0,
-        };
-        let distance = cmd[1..].parse::<i32>().unwrap();
+    for &(direction, distance) in steps {
+        facing = turn(facing, direction, vectors.len());
         for _ in 0..distance {
             // Each step is is a grid corner we visited
             position += &vectors[facing];
             let here = (position[0], position[1]);
-            if visited.get(&here).is_some() {
+            if visited.contains(&here) {
                 return position[0].abs() + position[1].abs();
             }
             visited.insert(here);
@@ -68,5 +83,7 @@ fn solve2(input: &str) -> i32 {
 
 #[test]
 fn test_solve2() {
-    assert_eq!(solve2("R8, R4, R4, R8"), 4)
+    assert_eq!(solve2(&generate("R8, R4, R4, R8").unwrap()), 4)
 }
+
+crate::solution::solution!(Day01 = 1, Vec<(Turn, i32)>, generate, solve, solve2);
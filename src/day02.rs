@@ -67,3 +67,5 @@ fn solve2(input: &str) -> String {
 fn test_solve2() {
     assert_eq!(&solve2(include_str!("day02_example.txt")), "5DB3");
 }
+
+crate::solution::solution!(Day02 = 2, String, crate::solution::text, solve, solve2);
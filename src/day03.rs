@@ -1,53 +1,50 @@
-use itertools::Itertools;
+use crate::input::{parse_lines, InputError, Malformed};
+
+fn parse_triangle(line: &str) -> Result<[i32; 3], Malformed> {
+    let sides = line
+        .split_ascii_whitespace()
+        .map(str::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    sides
+        .try_into()
+        .map_err(|_| Malformed::Expected("three sides"))
+}
+
+#[aoc_generator(day3)]
+fn generate(input: &str) -> Result<Vec<[i32; 3]>, InputError> {
+    parse_lines(3, input, parse_triangle)
+}
+
+fn possible(mut sides: [i32; 3]) -> bool {
+    sides.sort();
+    sides[0] as i64 + sides[1] as i64 > sides[2] as i64
+}
 
 #[aoc(day3, part1)]
-fn solve(input: &str) -> usize {
-    input
-        .lines()
-        .map(|s| {
-            s.split_ascii_whitespace()
-                .map(|s| s.parse::<i32>().unwrap())
-                .sorted()
-                .collect_vec()
-        })
-        .filter(|t| t[0] + t[1] > t[2])
-        .count()
+fn solve(triangles: &[[i32; 3]]) -> usize {
+    triangles.iter().filter(|&&t| possible(t)).count()
 }
 
 #[test]
 fn test_solve() {
-    assert_eq!(solve("5 10 25"), 0);
+    assert_eq!(solve(&generate("5 10 25").unwrap()), 0);
 }
 
+// Read down the columns, three rows at a time.  Rows left over at the end
+// can't make a whole triangle.
 #[aoc(day3, part2)]
-fn solve2(input: &str) -> usize {
-    input
-        .lines()
-        .chunks(3)
-        .into_iter()
-        .flat_map(|c| {
-            let square = c
-                .map(|l| {
-                    l.split_ascii_whitespace()
-                        .map(|v| v.parse::<i32>().unwrap())
-                        .collect_vec()
-                })
-                .collect_vec();
-            vec![
-                vec![square[0][0], square[1][0], square[2][0]],
-                vec![square[0][1], square[1][1], square[2][1]],
-                vec![square[0][2], square[1][2], square[2][2]],
-            ]
-        })
-        .map(|mut t| {
-            t.sort();
-            t
-        })
-        .filter(|t| t[0] + t[1] > t[2])
+fn solve2(triangles: &[[i32; 3]]) -> usize {
+    triangles
+        .chunks_exact(3)
+        .flat_map(|rows| (0..3).map(move |i| [rows[0][i], rows[1][i], rows[2][i]]))
+        .filter(|&t| possible(t))
         .count()
 }
 
 #[test]
 fn test_solve2() {
-    assert_eq!(solve2(include_str!("day03_example.txt")), 6);
+    let triangles = generate(include_str!("day03_example.txt")).unwrap();
+    assert_eq!(solve2(&triangles), 6);
 }
+
+crate::solution::solution!(Day03 = 3, Vec<[i32; 3]>, generate, solve, solve2);
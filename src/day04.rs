@@ -1,22 +1,36 @@
+use std::str::FromStr;
+
 use itertools::Itertools;
 
+use crate::input::{parse_lines, InputError, Malformed};
+
 #[derive(Debug)]
-struct Room {
+pub struct Room {
     name: String,
     sector: u32,
     checksum: String,
 }
 
-impl From<&str> for Room {
-    fn from(input: &str) -> Self {
-        let (name, rest) = input.rsplit_once('-').unwrap();
-        let (sector, checksum) = rest.split_once('[').unwrap();
+impl FromStr for Room {
+    type Err = Malformed;
 
-        Self {
-            name: name.to_string(),
-            sector: sector.parse().unwrap(),
-            checksum: checksum[0..5].to_string(),
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (name, rest) = input
+            .rsplit_once('-')
+            .ok_or(Malformed::Expected("a sector id"))?;
+        let (sector, checksum) = rest
+            .split_once('[')
+            .ok_or(Malformed::Expected("a checksum"))?;
+        // Decrypting only works on letters
+        if !name.chars().all(|c| c == '-' || c.is_ascii_lowercase()) {
+            return Err(Malformed::Expected("a lowercase name"));
         }
+
+        Ok(Self {
+            name: name.to_string(),
+            sector: sector.parse()?,
+            checksum: checksum.chars().take(5).collect(),
+        })
     }
 }
 
@@ -44,7 +58,10 @@ impl Room {
 #[test]
 fn test_room_checksum() {
     assert_eq!(
-        &Room::from("aaaaa-bbb-z-y-x-123[abxyz]").checksum(),
+        &"aaaaa-bbb-z-y-x-123[abxyz]"
+            .parse::<Room>()
+            .unwrap()
+            .checksum(),
         "abxyz"
     )
 }
@@ -68,14 +85,17 @@ impl Room {
 #[test]
 fn test_room_decrypt() {
     assert_eq!(
-        &Room::from("qzmt-zixmtkozy-ivhz-343[aaaaa]").decrypt(),
+        &"qzmt-zixmtkozy-ivhz-343[aaaaa]"
+            .parse::<Room>()
+            .unwrap()
+            .decrypt(),
         "very encrypted name"
     )
 }
 
 #[aoc_generator(day4)]
-fn generate(input: &str) -> Vec<Room> {
-    input.lines().map(Room::from).collect()
+fn generate(input: &str) -> Result<Vec<Room>, InputError> {
+    parse_lines(4, input, str::parse)
 }
 
 #[aoc(day4, part1)]
@@ -91,3 +111,5 @@ fn solve2(rooms: &[Room]) -> u32 {
         .map(|r| r.sector)
         .sum()
 }
+
+crate::solution::solution!(Day04 = 4, Vec<Room>, generate, solve, solve2);
@@ -27,7 +27,7 @@ fn test_solve() {
 fn solve2(input: &str) -> String {
     let mut interesting = 0;
     let mut answer = ['_'; 8];
-    while answer.iter().any(|&c| c == '_') {
+    while answer.contains(&'_') {
         let digest = md5::compute(format!("{input}{interesting}").as_bytes());
         let hex = format!("{:x}", digest);
         interesting += 1;
@@ -40,7 +40,7 @@ fn solve2(input: &str) -> String {
             if answer[index] == '_' {
                 let digit = hex.chars().nth(6).unwrap();
                 answer[index] = digit;
-                println!("{} {}", interesting, String::from_iter(answer));
+                eprintln!("{} {}", interesting, String::from_iter(answer));
             }
         }
     }
@@ -52,3 +52,5 @@ fn solve2(input: &str) -> String {
 fn test_solve2() {
     assert_eq!(&solve2("abc"), "05ace8e3");
 }
+
+crate::solution::solution!(Day05 = 5, String, crate::solution::text, solve, solve2);
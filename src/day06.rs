@@ -38,10 +38,12 @@ fn test_solve() {
 
 #[aoc(day6, part2)]
 fn solve2(input: &str) -> String {
-    solver(input, |av, bv| Ord::cmp(av, bv))
+    solver(input, Ord::cmp)
 }
 
 #[test]
 fn test_solve2() {
     assert_eq!(solve2(include_str!("day06_example.txt")), "advent");
 }
+
+crate::solution::solution!(Day06 = 6, String, crate::solution::text, solve, solve2);
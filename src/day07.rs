@@ -1,35 +1,51 @@
-#[aoc(day7, part1)]
-fn solve(input: &str) -> usize {
-    input.lines().filter(|l| has_tls(l)).count()
-}
+use crate::input::{parse_lines, InputError, Malformed};
 
-#[aoc(day7, part2)]
-fn solve2(input: &str) -> usize {
-    input.lines().filter(|l| has_ssl(l)).count()
+// The parts of an address outside square brackets, and those inside
+#[derive(Debug, PartialEq, Eq)]
+pub struct Address {
+    addrs: Vec<String>,
+    nets: Vec<String>,
 }
 
-fn parse_addr(addr: &str) -> (Vec<&str>, Vec<&str>) {
+fn parse_addr(addr: &str) -> Result<Address, Malformed> {
     let mut addrs = vec![];
     let mut nets = vec![];
     let mut a = addr;
     while let Some((left, rem)) = a.split_once('[') {
-        addrs.push(left);
-        let (net, rest) = rem.split_once(']').unwrap();
-        nets.push(net);
+        addrs.push(left.to_string());
+        let (net, rest) = rem
+            .split_once(']')
+            .ok_or(Malformed::Expected("a `]` after every `[`"))?;
+        nets.push(net.to_string());
         a = rest
     }
-    addrs.push(a);
-    (addrs, nets)
+    addrs.push(a.to_string());
+    Ok(Address { addrs, nets })
+}
+
+#[aoc_generator(day7)]
+fn generate(input: &str) -> Result<Vec<Address>, InputError> {
+    parse_lines(7, input, parse_addr)
+}
+
+#[aoc(day7, part1)]
+fn solve(addrs: &[Address]) -> usize {
+    addrs.iter().filter(|a| has_tls(a)).count()
+}
+
+#[aoc(day7, part2)]
+fn solve2(addrs: &[Address]) -> usize {
+    addrs.iter().filter(|a| has_ssl(a)).count()
 }
 
-fn has_tls(addr: &str) -> bool {
-    let (addrs, nets) = parse_addr(addr);
-    !nets.into_iter().any(has_abba) && addrs.into_iter().any(has_abba)
+fn has_tls(addr: &Address) -> bool {
+    let abba = |s: &String| has_abba(s);
+    !addr.nets.iter().any(abba) && addr.addrs.iter().any(abba)
 }
 
 fn has_abba(s: &str) -> bool {
     let b = s.as_bytes();
-    for i in 0..b.len() - 3 {
+    for i in 0..b.len().saturating_sub(3) {
         if b[i] == b[i + 3] && b[i + 1] == b[i + 2] && b[i] != b[i + 1] {
             return true;
         }
@@ -39,17 +55,17 @@ fn has_abba(s: &str) -> bool {
 
 #[test]
 fn test_has_tls() {
-    assert!(has_tls("abba[mnop]qrst"));
-    assert!(!has_tls("abcd[bddb]xyyx"));
-    assert!(!has_tls("aaaa[qwer]tyui"));
-    assert!(has_tls("ioxxoj[asdfgh]zxcvbn"));
-    assert!(!has_tls("abba[mnop]qrst[abba]foos"));
+    assert!(has_tls(&parse_addr("abba[mnop]qrst").unwrap()));
+    assert!(!has_tls(&parse_addr("abcd[bddb]xyyx").unwrap()));
+    assert!(!has_tls(&parse_addr("aaaa[qwer]tyui").unwrap()));
+    assert!(has_tls(&parse_addr("ioxxoj[asdfgh]zxcvbn").unwrap()));
+    assert!(!has_tls(&parse_addr("abba[mnop]qrst[abba]foos").unwrap()));
 }
 
 fn net_aba(addr: &str) -> Vec<String> {
     let mut found = vec![];
     let b = addr.as_bytes();
-    for i in 0..b.len() - 2 {
+    for i in 0..b.len().saturating_sub(2) {
         if b[i] == b[i + 2] && b[i] != b[i + 1] {
             // We found an aba, record the corresponding bab
             found.push(String::from_iter(vec![
@@ -62,10 +78,9 @@ fn net_aba(addr: &str) -> Vec<String> {
     found
 }
 
-fn has_ssl(addr: &str) -> bool {
-    let (addrs, nets) = parse_addr(addr);
-    for aba in nets.into_iter().flat_map(net_aba) {
-        if addrs.iter().any(|s| s.contains(&aba)) {
+fn has_ssl(addr: &Address) -> bool {
+    for aba in addr.nets.iter().flat_map(|net| net_aba(net)) {
+        if addr.addrs.iter().any(|s| s.contains(&aba)) {
             return true;
         }
     }
@@ -74,8 +89,10 @@ fn has_ssl(addr: &str) -> bool {
 
 #[test]
 fn test_has_ssl() {
-    assert!(has_ssl("aba[bab]xyz"));
-    assert!(!has_ssl("xyx[xyx]xyx"));
-    assert!(has_ssl("aaa[kek]eke"));
-    assert!(has_ssl("zazbz[bzb]cdb"));
+    assert!(has_ssl(&parse_addr("aba[bab]xyz").unwrap()));
+    assert!(!has_ssl(&parse_addr("xyx[xyx]xyx").unwrap()));
+    assert!(has_ssl(&parse_addr("aaa[kek]eke").unwrap()));
+    assert!(has_ssl(&parse_addr("zazbz[bzb]cdb").unwrap()));
 }
+
+crate::solution::solution!(Day07 = 7, Vec<Address>, generate, solve, solve2);
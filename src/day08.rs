@@ -1,7 +1,9 @@
 use itertools::{iproduct, Itertools};
 use ndarray::prelude::*;
 
-struct Lcd {
+use crate::input::{parse_lines, InputError, Malformed};
+
+pub struct Lcd {
     data: Array2<bool>,
 }
 
@@ -36,29 +38,50 @@ impl Lcd {
         self.data.iter().filter(|&c| *c).count()
     }
 
-    fn apply(&mut self, c: &str) {
+    fn apply(&mut self, c: &str) -> Result<(), Malformed> {
         let toks = c.split_ascii_whitespace().collect_vec();
-        if toks[0] == "rect" {
-            let (w, h) = toks[1].split_once('x').unwrap();
-            for (x, y) in iproduct!(0..w.parse().unwrap(), 0..h.parse().unwrap()) {
-                self.data[[y, x]] = true;
+        let (axis, index, by) = match toks[..] {
+            ["rect", size] => {
+                let (w, h) = size
+                    .split_once('x')
+                    .ok_or(Malformed::Expected("a size like 3x2"))?;
+                let (w, h) = (w.parse()?, h.parse()?);
+                if w > self.data.ncols() || h > self.data.nrows() {
+                    return Err(Malformed::Expected("a rectangle that fits"));
+                }
+                for (x, y) in iproduct!(0..w, 0..h) {
+                    self.data[[y, x]] = true;
+                }
+                return Ok(());
             }
-            return;
-        }
+            ["rotate", axis @ ("row" | "column"), index, "by", by] => (axis, index, by),
+            _ => return Err(Malformed::Expected("`rect` or `rotate`")),
+        };
 
-        let (_, index) = toks[2].split_once('=').unwrap();
-        let index = index.parse().unwrap();
-        let by: usize = toks[4].parse().unwrap();
-        let source = match toks[1] {
+        let (_, index) = index
+            .split_once('=')
+            .ok_or(Malformed::Expected("x= or y="))?;
+        let index = index.parse()?;
+        let by: usize = by.parse()?;
+        let lines = match axis {
+            "column" => self.data.ncols(),
+            _ => self.data.nrows(),
+        };
+        if index >= lines {
+            return Err(Malformed::Expected("a row or column on the screen"));
+        }
+        let source = match axis {
             "column" => self.data.column_mut(index),
-            "row" => self.data.row_mut(index),
-            _ => unreachable!(),
+            _ => self.data.row_mut(index),
         };
+        // Only how far round it ends up matters, however far it's turned
+        let by = by % source.len();
         let mut rotated: Array1<bool> = Array::default((source.len(),));
         for i in 0..source.len() {
             rotated[(i + by) % source.len()] = source[i];
         }
         rotated.assign_to(source);
+        Ok(())
     }
 }
 
@@ -67,7 +90,7 @@ fn test_pixels() {
     let mut display = Lcd::new(7, 3);
     assert_eq!(display.lit(), 0);
 
-    display.apply("rect 3x2");
+    display.apply("rect 3x2").unwrap();
     let example = r#"
 ###....
 ###....
@@ -77,7 +100,7 @@ fn test_pixels() {
     assert_eq!(display, example.trim());
     assert_eq!(display.lit(), 6);
 
-    display.apply("rotate column x=1 by 1");
+    display.apply("rotate column x=1 by 1").unwrap();
     let example = r#"
 #.#....
 ###....
@@ -85,15 +108,25 @@ fn test_pixels() {
     "#;
 
     assert_eq!(display, example.trim());
+
+    display.apply("rotate row y=0 by 18446744073709551615").unwrap();
+    let example = r#"
+.#.#...
+###....
+.#.....
+    "#;
+
+    assert_eq!(display, example.trim());
+
+    assert!(display.apply("rect 8x1").is_err());
+    assert!(display.apply("rotate row y=3 by 1").is_err());
 }
 
 #[aoc_generator(day8)]
-fn generate(input: &str) -> Lcd {
+fn generate(input: &str) -> Result<Lcd, InputError> {
     let mut display = Lcd::new(50, 6);
-    for c in input.lines() {
-        display.apply(c);
-    }
-    display
+    parse_lines(8, input, |c| display.apply(c))?;
+    Ok(display)
 }
 
 #[aoc(day8, part1)]
@@ -105,3 +138,5 @@ fn solve(display: &Lcd) -> usize {
 fn solve2(display: &Lcd) -> String {
     format!("\n{:?}", display)
 }
+
+crate::solution::solution!(Day08 = 8, Lcd, generate, solve, solve2);
@@ -1,66 +1,129 @@
-#[aoc(day9, part1)]
-fn solve(mut input: &[u8]) -> usize {
-    let mut count = 0;
-    while !input.is_empty() {
-        if input[0] == b'(' {
-            let end = input.iter().position(|&c| c == b')').unwrap();
-            let (size, repeated) = std::str::from_utf8(&input[1..end])
-                .unwrap()
-                .split_once('x')
-                .unwrap();
-            let size: usize = size.parse().unwrap();
-            let repeated: usize = repeated.parse().unwrap();
+use crate::input::{parse_lines, InputError, Malformed};
 
-            input = &input[end + size + 1..];
-            count += size * repeated;
-        } else {
-            input = &input[1..];
-            count += 1;
-        }
+// Only lengths matter, so plain text is kept as just how long it is.  What a
+// marker repeats is only read for markers of its own in part 2, so it's kept
+// as it is.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Chunk {
+    Text(usize),
+    Marker {
+        size: usize,
+        repeated: usize,
+        section: String,
+    },
+}
+
+// The marker `text` starts with, less its `(`: its size, how many times it
+// repeats, and the rest of the text from the marked section on
+fn marker(text: &str) -> Result<(usize, usize, &str), Malformed> {
+    let (marker, after) = text
+        .split_once(')')
+        .ok_or(Malformed::Expected("a marker like (3x2)"))?;
+    let (size, repeated) = marker
+        .split_once('x')
+        .ok_or(Malformed::Expected("a marker like (3x2)"))?;
+    let size = size.parse()?;
+    if after.get(..size).is_none() {
+        return Err(Malformed::Expected("the marked section to fit in the line"));
     }
-    count
+    Ok((size, repeated.parse()?, after))
+}
+
+fn parse_chunks(input: &str) -> Result<Vec<Chunk>, String> {
+    let mut chunks = vec![];
+    let mut rest = input;
+    while !rest.is_empty() {
+        let Some(text) = rest.strip_prefix('(') else {
+            let end = rest.find('(').unwrap_or(rest.len());
+            chunks.push(Chunk::Text(end));
+            rest = &rest[end..];
+            continue;
+        };
+        let column = input.len() - rest.len() + 1;
+        let (size, repeated, after) = marker(text).map_err(|e| format!("column {column}: {e}"))?;
+        chunks.push(Chunk::Marker {
+            size,
+            repeated,
+            section: after[..size].to_string(),
+        });
+        rest = &after[size..];
+    }
+    Ok(chunks)
+}
+
+#[aoc_generator(day9)]
+fn generate(input: &str) -> Result<Vec<Chunk>, InputError> {
+    let lines = parse_lines(9, input, parse_chunks)?;
+    Ok(lines.into_iter().flatten().collect())
+}
+
+// Markers inside a repeated section are just more text
+#[aoc(day9, part1)]
+fn solve(chunks: &[Chunk]) -> usize {
+    chunks
+        .iter()
+        .map(|chunk| match chunk {
+            Chunk::Text(len) => *len,
+            Chunk::Marker { size, repeated, .. } => size * repeated,
+        })
+        .sum()
 }
 
 #[test]
 fn test_solve() {
-    assert_eq!(solve(b"ADVENT"), 6);
-    assert_eq!(solve(b"A(1x5)BC"), 7);
-    assert_eq!(solve(b"(3x3)XYZ"), 9);
-    assert_eq!(solve(b"A(2x2)BCD(2x2)EFG"), 11);
-    assert_eq!(solve(b"(6x1)(1x3)A"), 6);
-    assert_eq!(solve(b"X(8x2)(3x3)ABCY"), 18);
+    let solve = |input| solve(&generate(input).unwrap());
+    assert_eq!(solve("ADVENT"), 6);
+    assert_eq!(solve("A(1x5)BC"), 7);
+    assert_eq!(solve("(3x3)XYZ"), 9);
+    assert_eq!(solve("A(2x2)BCD(2x2)EFG"), 11);
+    assert_eq!(solve("(6x1)(1x3)A"), 6);
+    assert_eq!(solve("X(8x2)(3x3)ABCY"), 18);
+    assert_eq!(solve("(3x1)(1x"), 3);
 }
 
-#[aoc(day9, part2)]
-fn solve2(mut input: &[u8]) -> usize {
+// How long a marked section comes out.  Something in it that isn't quite a
+// marker was never going to be expanded, so it's only text.
+fn decompressed(text: &str) -> usize {
     let mut count = 0;
-    while !input.is_empty() {
-        if input[0] == b'(' {
-            let end = input.iter().position(|&c| c == b')').unwrap();
-            let (size, repeated) = std::str::from_utf8(&input[1..end])
-                .unwrap()
-                .split_once('x')
-                .unwrap();
-            let size: usize = size.parse().unwrap();
-            let repeated: usize = repeated.parse().unwrap();
-
-            count += repeated * solve2(&input[end + 1..end + size + 1]);
-            input = &input[end + size + 1..];
-        } else {
-            count += 1;
-            input = &input[1..];
+    let mut rest = text;
+    while let Some(start) = rest.find('(') {
+        count += start;
+        rest = &rest[start + 1..];
+        match marker(rest) {
+            Ok((size, repeated, after)) => {
+                count += repeated * decompressed(&after[..size]);
+                rest = &after[size..];
+            }
+            Err(_) => count += 1,
         }
     }
-    count
+    count + rest.len()
+}
+
+#[aoc(day9, part2)]
+fn solve2(chunks: &[Chunk]) -> usize {
+    chunks
+        .iter()
+        .map(|chunk| match chunk {
+            Chunk::Text(len) => *len,
+            Chunk::Marker {
+                repeated, section, ..
+            } => repeated * decompressed(section),
+        })
+        .sum()
 }
 
 #[test]
 fn test_solve2() {
-    assert_eq!(solve2(b"(3x3)XYZ"), 9);
-    assert_eq!(solve2(b"X(8x2)(3x3)ABCY"), 20);
-    assert_eq!(solve2(b"(27x12)(20x12)(13x14)(7x10)(1x12)A"), 241920);
+    let solve2 = |input| solve2(&generate(input).unwrap());
+    assert_eq!(solve2("(3x3)XYZ"), 9);
+    assert_eq!(solve2("X(8x2)(3x3)ABCY"), 20);
+    assert_eq!(solve2("(27x12)(20x12)(13x14)(7x10)(1x12)A"), 241920);
     assert_eq!(
-        solve2(b"(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN"),
+        solve2("(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN"),
         445
     );
+    assert_eq!(solve2("(3x1)(1x"), 3);
 }
+
+crate::solution::solution!(Day09 = 9, Vec<Chunk>, generate, solve, solve2);
@@ -2,6 +2,8 @@ use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 
+use crate::input::{parse_lines, InputError, Malformed};
+
 #[derive(Debug, Default, Clone, Copy)]
 enum Destination {
     #[default]
@@ -11,14 +13,13 @@ enum Destination {
 }
 
 impl std::str::FromStr for Destination {
-    type Err = Box<dyn std::error::Error>;
+    type Err = Malformed;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (kind, number) = s.split_once(' ').unwrap();
-        match kind {
-            "bot" => Ok(Destination::Bot(number.parse()?)),
-            "output" => Ok(Destination::Output(number.parse()?)),
-            _ => unreachable!(),
+        match s.split_once(' ') {
+            Some(("bot", number)) => Ok(Destination::Bot(number.parse()?)),
+            Some(("output", number)) => Ok(Destination::Output(number.parse()?)),
+            _ => Err(Malformed::Expected("a bot or an output")),
         }
     }
 }
@@ -53,7 +54,7 @@ impl Bot {
 }
 
 #[derive(Default, Debug, Clone)]
-struct Machine {
+pub struct Machine {
     outputs: HashMap<usize, usize>,
     bots: HashMap<usize, Bot>,
 }
@@ -73,9 +74,7 @@ impl Machine {
 
         for (dest, value) in distribute {
             match dest {
-                Destination::Bot(other) => {
-                    self.bots.entry(other).or_insert_with(Bot::new).take(value)
-                }
+                Destination::Bot(other) => self.bots.entry(other).or_default().take(value),
                 Destination::Output(out) => {
                     self.outputs.insert(out, value);
                 }
@@ -106,14 +105,14 @@ impl Machine {
 }
 
 #[aoc_generator(day10)]
-fn generate(input: &str) -> Machine {
+fn generate(input: &str) -> Result<Machine, InputError> {
     let mut m = Machine::default();
-    for l in input.lines() {
+    parse_lines(10, input, |l| {
         let toks = l.split_ascii_whitespace().collect_vec();
-        match toks[0] {
-            "value" => {
-                let v = toks[1].parse().unwrap();
-                let bot = toks[5].parse().unwrap();
+        match toks[..] {
+            ["value", v, "goes", "to", "bot", bot] => {
+                let v = v.parse()?;
+                let bot = bot.parse()?;
                 m.bots
                     .entry(bot)
                     .and_modify(|b| b.take(v))
@@ -123,10 +122,10 @@ fn generate(input: &str) -> Machine {
                         b
                     });
             }
-            "bot" => {
-                let source = toks[1].parse().unwrap();
-                let low = toks[5..=6].join(" ").parse().unwrap();
-                let high = toks[10..=11].join(" ").parse().unwrap();
+            ["bot", source, "gives", "low", "to", _, _, "and", "high", "to", _, _] => {
+                let source = source.parse()?;
+                let low = toks[5..=6].join(" ").parse()?;
+                let high = toks[10..=11].join(" ").parse()?;
                 m.bots
                     .entry(source)
                     .and_modify(|b| b.gives(low, high))
@@ -136,10 +135,11 @@ fn generate(input: &str) -> Machine {
                         b
                     });
             }
-            _ => {}
+            _ => return Err(Malformed::Expected("a value or a bot")),
         }
-    }
-    m
+        Ok(())
+    })?;
+    Ok(m)
 }
 
 #[aoc(day10, part1)]
@@ -150,7 +150,9 @@ fn solve(machine: &Machine) -> usize {
 #[test]
 fn test_example() {
     assert_eq!(
-        generate(include_str!("day10_example.txt")).who_compares(2, 5),
+        generate(include_str!("day10_example.txt"))
+            .unwrap()
+            .who_compares(2, 5),
         2
     );
 }
@@ -159,3 +161,5 @@ fn test_example() {
 fn solve2(machine: &Machine) -> usize {
     machine.products()
 }
+
+crate::solution::solution!(Day10 = 10, Machine, generate, solve, solve2);
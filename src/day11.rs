@@ -5,6 +5,8 @@ use lazy_static::lazy_static;
 use pathfinding::prelude::dijkstra;
 use regex::Regex;
 
+use crate::input::{parse_lines, InputError, Malformed};
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Ord, PartialOrd)]
 enum Item {
     Generator(String),
@@ -14,7 +16,7 @@ enum Item {
 #[derive(Debug, Default, PartialEq, Eq, Clone)]
 struct Floor(HashSet<Item>);
 
-#[allow(clippy::derive_hash_xor_eq)]
+#[allow(clippy::derived_hash_with_manual_eq)]
 impl std::hash::Hash for Floor {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.0.iter().sorted().for_each(|i| i.hash(state));
@@ -57,43 +59,52 @@ fn test_floor_safe() {
     assert!(floor.safe(), "Matched chip to make it safe");
 }
 
+// Not `Factory`, which aoc-runner's generated code takes for itself
 #[derive(Debug, Default, PartialEq, Eq, Clone, Hash)]
-struct Factory {
+pub struct Facility {
     lift: usize,
     floors: [Floor; 4],
 }
 
-#[derive(Debug)]
-enum FactoryParseError {}
-
-impl std::str::FromStr for Factory {
-    type Err = FactoryParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut factory = Factory::default();
-        for (floor, contents) in s.lines().enumerate() {
-            lazy_static! {
-                static ref CHIP: Regex = Regex::new(r"a (\S+)-compatible microchip").unwrap();
-            }
-            lazy_static! {
-                static ref GEN: Regex = Regex::new(r"a (\S+) generator").unwrap();
-            }
-            CHIP.captures_iter(contents).for_each(|c| {
-                factory.floors[floor].add(Item::Chip(c.get(1).unwrap().as_str().to_string()))
-            });
-            GEN.captures_iter(contents).for_each(|c| {
-                factory.floors[floor].add(Item::Generator(c.get(1).unwrap().as_str().to_string()))
-            });
-        }
-        Ok(factory)
+fn parse_floor(contents: &str) -> Result<Floor, Malformed> {
+    lazy_static! {
+        static ref CHIP: Regex = Regex::new(r"a (\S+)-compatible microchip").unwrap();
+    }
+    lazy_static! {
+        static ref GEN: Regex = Regex::new(r"a (\S+) generator").unwrap();
+    }
+    if !contents.contains(" floor contains ") {
+        return Err(Malformed::Expected(
+            "a line like `The first floor contains ...`",
+        ));
+    }
+    let mut floor = Floor::default();
+    CHIP.captures_iter(contents)
+        .for_each(|c| floor.add(Item::Chip(c[1].to_string())));
+    GEN.captures_iter(contents)
+        .for_each(|c| floor.add(Item::Generator(c[1].to_string())));
+    Ok(floor)
+}
+
+#[aoc_generator(day11)]
+fn generate(input: &str) -> Result<Facility, InputError> {
+    let mut factory = Facility::default();
+    let floors = parse_lines(11, input, parse_floor)?;
+    if let Some(extra) = input.lines().nth(factory.floors.len()) {
+        let reason = Malformed::Expected("only four floors");
+        return Err(InputError::new(11, factory.floors.len() + 1, extra, reason));
+    }
+    for (i, floor) in floors.into_iter().enumerate() {
+        factory.floors[i] = floor;
     }
+    Ok(factory)
 }
 
 #[cfg(test)]
 #[test]
 fn test_factory_from_str() {
-    let parsed: Factory = include_str!("day11_example.txt").parse().unwrap();
-    let mut factory = Factory::default();
+    let parsed = generate(include_str!("day11_example.txt")).unwrap();
+    let mut factory = Facility::default();
     factory.floors[0].add(Item::Chip("hydrogen".to_string()));
     factory.floors[0].add(Item::Chip("lithium".to_string()));
     factory.floors[1].add(Item::Generator("hydrogen".to_string()));
@@ -101,7 +112,7 @@ fn test_factory_from_str() {
     assert_eq!(parsed, factory);
 }
 
-impl Factory {
+impl Facility {
     fn legal(&self) -> bool {
         self.floors.iter().all(|f| f.safe())
     }
@@ -152,7 +163,7 @@ impl Factory {
 #[cfg(test)]
 #[test]
 fn test_factory_moves() {
-    let factory: Factory = include_str!("day11_example.txt").parse().unwrap();
+    let factory = generate(include_str!("day11_example.txt")).unwrap();
     let moves = factory.moves();
     assert_eq!(moves.len(), 2, "There are two legal moves");
     assert!(
@@ -164,21 +175,20 @@ fn test_factory_moves() {
 #[cfg(test)]
 #[test]
 fn test_factory_solved() {
-    assert!(Factory::default().solved(), "Empty factories are solved");
+    assert!(Facility::default().solved(), "Empty factories are solved");
 
-    let factory: Factory = include_str!("day11_example.txt").parse().unwrap();
+    let factory = generate(include_str!("day11_example.txt")).unwrap();
     assert!(!factory.solved(), "Example factory does not start solved");
 
-    let mut factory = Factory::default();
+    let mut factory = Facility::default();
     factory.floors[3].add(Item::Chip("Green".to_string()));
     assert!(factory.solved(), "Solved when everything on the top floor");
 }
 
 #[aoc(day11, part1)]
-fn solve(input: &str) -> usize {
-    let factory: Factory = input.parse().unwrap();
+fn solve(factory: &Facility) -> usize {
     if let Some((_path, cost)) = dijkstra(
-        &factory,
+        factory,
         |f| f.moves().into_iter().map(|m| (m, 1)).collect_vec(),
         |f| f.solved(),
     ) {
@@ -193,12 +203,15 @@ fn solve(input: &str) -> usize {
 #[test]
 #[ignore]
 fn test_solve() {
-    assert_eq!(solve(include_str!("day11_example.txt")), 11);
+    assert_eq!(
+        solve(&generate(include_str!("day11_example.txt")).unwrap()),
+        11
+    );
 }
 
 #[aoc(day11, part2)]
-fn solve2(input: &str) -> usize {
-    let mut factory: Factory = input.parse().unwrap();
+fn solve2(factory: &Facility) -> usize {
+    let mut factory = factory.clone();
     factory.floors[0].add(Item::Chip("elerium".to_string()));
     factory.floors[0].add(Item::Generator("elerium".to_string()));
     factory.floors[0].add(Item::Chip("dilithium".to_string()));
@@ -215,3 +228,5 @@ fn solve2(input: &str) -> usize {
         0
     }
 }
+
+crate::solution::solution!(Day11 = 11, Facility, generate, solve, solve2);
@@ -1,27 +1,47 @@
 use crate::assembunny::*;
+use crate::input::{parse_program, InputError};
 
 #[aoc_generator(day12)]
-fn generate(input: &str) -> Vec<Instruction> {
-    input.lines().map(|l| l.parse().unwrap()).collect()
+fn generate(input: &str) -> Result<Vec<Instruction>, InputError> {
+    parse_program(12, input)
 }
 
 #[aoc(day12, part1)]
-fn solve(program: &[Instruction]) -> i32 {
+fn solve(program: &[Instruction]) -> i64 {
     let mut cpu = Cpu::new(program.to_vec());
+    cpu.optimize();
     cpu.run();
-    cpu.get('a')
+    cpu.get('a').unwrap()
+}
+
+#[aoc(day12, part1, compiled)]
+pub(crate) fn solve_compiled(program: &[Instruction]) -> i64 {
+    let mut cpu = Cpu::new(program.to_vec());
+    cpu.set_backend(Backend::Compiled);
+    cpu.run();
+    cpu.get('a').unwrap()
 }
 
 #[cfg(test)]
 #[test]
 fn test_solve() {
-    assert_eq!(solve(&generate(include_str!("day12_example.txt"))), 42);
+    assert_eq!(
+        solve(&generate(include_str!("day12_example.txt")).unwrap()),
+        42
+    );
+    assert_eq!(
+        solve_compiled(&generate(include_str!("day12_example.txt")).unwrap()),
+        42
+    );
 }
 
 #[aoc(day12, part2)]
-fn solve2(program: &[Instruction]) -> i32 {
+fn solve2(program: &[Instruction]) -> i64 {
     let mut cpu = Cpu::new(program.to_vec());
-    cpu.set('c', 1);
+    cpu.optimize();
+    cpu.set('c', 1).unwrap();
     cpu.run();
-    cpu.get('a')
+    cpu.get('a').unwrap()
 }
+
+crate::solution::solution!(Day12 = 12, Vec<Instruction>, generate, solve, solve2);
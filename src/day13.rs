@@ -2,6 +2,8 @@ use itertools::iproduct;
 use itertools::Itertools;
 use pathfinding::prelude::dijkstra;
 
+use crate::input::InputError;
+
 fn is_wall(seed: i32, x: i32, y: i32) -> bool {
     if x < 0 || y < 0 {
         true
@@ -40,14 +42,18 @@ fn test_find_route() {
     assert_eq!(find_route(10, 7, 4), 11);
 }
 
+#[aoc_generator(day13)]
+fn generate(input: &str) -> Result<i32, InputError> {
+    input.parse().map_err(|e| InputError::new(13, 1, input, e))
+}
+
 #[aoc(day13, part1)]
-fn solve(input: &str) -> usize {
-    find_route(input.parse().unwrap(), 31, 39)
+fn solve(&seed: &i32) -> usize {
+    find_route(seed, 31, 39)
 }
 
 #[aoc(day13, part2)]
-fn solve2(input: &str) -> usize {
-    let seed = input.parse().unwrap();
+fn solve2(&seed: &i32) -> usize {
     iproduct!(0..=50, 0..=50)
         .filter(|&(x, y)| !is_wall(seed, x, y))
         .flat_map(|goal| {
@@ -59,3 +65,5 @@ fn solve2(input: &str) -> usize {
         .unique()
         .count()
 }
+
+crate::solution::solution!(Day13 = 13, i32, generate, solve, solve2);
@@ -98,3 +98,5 @@ fn solve2(salt: &str) -> usize {
 fn test_solve2() {
     assert_eq!(solve2("abc"), 22859)
 }
+
+crate::solution::solution!(Day14 = 14, String, crate::solution::text, solve, solve2);
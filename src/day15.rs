@@ -3,25 +3,30 @@ use regex::Regex;
 
 use std::str::FromStr;
 
+use crate::input::{parse_lines, InputError, Malformed};
+
 #[derive(Debug, PartialEq, Clone)]
-struct Disc {
+pub struct Disc {
     start: usize,
     period: usize,
 }
 
-#[derive(Debug)]
-struct DiscParseErr {}
 impl FromStr for Disc {
-    type Err = DiscParseErr;
+    type Err = Malformed;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         lazy_static! {
             static ref RE: Regex =
                 Regex::new(r"has (\d+) positions; at time=0, it is at position (\d+)\.").unwrap();
         }
-        let captures = RE.captures(s).unwrap();
-        let period = captures.get(1).unwrap().as_str().parse().unwrap();
-        let start = captures.get(2).unwrap().as_str().parse().unwrap();
+        let captures = RE
+            .captures(s)
+            .ok_or(Malformed::Expected("a disc's positions"))?;
+        let period = captures[1].parse()?;
+        let start = captures[2].parse()?;
+        if period == 0 {
+            return Err(Malformed::Expected("at least one position"));
+        }
         Ok(Disc { start, period })
     }
 }
@@ -42,21 +47,21 @@ fn test_disc_from_str() {
     )
 }
 
-#[aoc_generator(day15, part1)]
-fn generate(s: &str) -> Vec<Disc> {
-    s.lines().map(|l| l.parse().unwrap()).collect()
+#[aoc_generator(day15)]
+fn generate(s: &str) -> Result<Vec<Disc>, InputError> {
+    parse_lines(15, s, str::parse)
 }
 
-#[aoc_generator(day15, part2)]
-fn generate2(s: &str) -> Vec<Disc> {
-    generate(&format!(
-        "{s}\n{}",
-        "Disc #99 has 11 positions; at time=0, it is at position 0."
-    ))
+fn with_extra_disc(mut discs: Vec<Disc>) -> Vec<Disc> {
+    // Disc #99 has 11 positions; at time=0, it is at position 0.
+    discs.push(Disc {
+        start: 0,
+        period: 11,
+    });
+    discs
 }
 
 #[aoc(day15, part1)]
-#[aoc(day15, part2)]
 fn solve(discs: &[Disc]) -> usize {
     'time: for time in 0.. {
         for (i, disc) in discs.iter().enumerate() {
@@ -69,8 +74,18 @@ fn solve(discs: &[Disc]) -> usize {
     unreachable!()
 }
 
+#[aoc(day15, part2)]
+fn solve2(discs: &[Disc]) -> usize {
+    solve(&with_extra_disc(discs.to_vec()))
+}
+
 #[cfg(test)]
 #[test]
 fn test_solve() {
-    assert_eq!(solve(&generate(include_str!("day15_example.txt"))), 5)
+    assert_eq!(
+        solve(&generate(include_str!("day15_example.txt")).unwrap()),
+        5
+    )
 }
+
+crate::solution::solution!(Day15 = 15, Vec<Disc>, generate, solve, solve2);
@@ -73,3 +73,5 @@ fn solve(seed: &str) -> String {
 fn solve2(seed: &str) -> String {
     fill_disk(seed, 35651584)
 }
+
+crate::solution::solution!(Day16 = 16, String, crate::solution::text, solve, solve2);
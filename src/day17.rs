@@ -170,3 +170,5 @@ fn longest_kglvqrro() {
 fn longest_ulqzkmiv() {
     assert_eq!(longest("ulqzkmiv"), 830)
 }
+
+crate::solution::solution!(Day17 = 17, String, crate::solution::text, shortest, longest);
@@ -81,3 +81,5 @@ fn solve(input: &str) -> usize {
 fn solve2(input: &str) -> usize {
     safe(input, 400000)
 }
+
+crate::solution::solution!(Day18 = 18, String, crate::solution::text, solve, solve2);
@@ -1,10 +1,16 @@
+use crate::input::InputError;
+
 #[aoc_generator(day19)]
-fn generate(input: &str) -> usize {
-    input.parse().unwrap()
+fn generate(input: &str) -> Result<usize, InputError> {
+    match input.parse() {
+        Ok(0) => Err(InputError::new(19, 1, input, "expected at least one elf")),
+        Ok(players) => Ok(players),
+        Err(e) => Err(InputError::new(19, 1, input, e)),
+    }
 }
 
 #[aoc(day19, part1, slice)]
-fn josephus(input: &usize) -> usize {
+pub(crate) fn josephus(input: &usize) -> usize {
     // First find: 2^m + l for the highest power of 2
     // Answer: is 2 * l + 1.
 
@@ -17,14 +23,14 @@ fn josephus(input: &usize) -> usize {
 }
 
 #[aoc(day19, part1, rotate)]
-fn josephus_fiddling(input: &usize) -> usize {
+pub(crate) fn josephus_fiddling(input: &usize) -> usize {
     let mut bits = format!("{:b}", *input);
     unsafe { bits.as_bytes_mut() }.rotate_left(1);
     usize::from_str_radix(&bits, 2).unwrap()
 }
 
 #[aoc(day19, part1, bitmath)]
-fn josephus_bitmath(input: &usize) -> usize {
+pub(crate) fn josephus_bitmath(input: &usize) -> usize {
     (*input ^ (1 << (usize::BITS - input.leading_zeros() - 1))) * 2 + 1
 }
 
@@ -48,6 +54,8 @@ fn joe2(players: &usize) -> usize {
     *players - power_of_three
 }
 
+crate::solution::solution!(Day19 = 19, usize, generate, josephus, joe2);
+
 // This idiom to emulate table-based tests is growing on me a bit - as distinct
 // functions they run as seperate threads, plus with some sympathetic naming you
 // can see the case at a glance.
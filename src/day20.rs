@@ -1,3 +1,5 @@
+use crate::input::{parse_lines, InputError, Malformed};
+
 #[derive(Debug)]
 struct Span {
     start: u32,
@@ -12,22 +14,20 @@ impl Span {
 }
 
 #[derive(Default, Debug)]
-struct Firewall {
+pub struct Firewall {
     blacklist: Vec<Span>,
 }
 
 #[aoc_generator(day20)]
-fn generate(input: &str) -> Firewall {
-    let mut fw = Firewall::default();
-    for line in input.lines() {
-        let (from, to) = line.split_once('-').unwrap();
-        let from = from.parse().unwrap();
-        let to = to.parse().unwrap();
-
-        fw.blacklist.push(Span::new(from, to));
-        fw.blacklist.sort_by(|a, b| Ord::cmp(&a.start, &b.start));
-    }
-    fw
+fn generate(input: &str) -> Result<Firewall, InputError> {
+    let mut blacklist = parse_lines(20, input, |line| {
+        let (from, to) = line
+            .split_once('-')
+            .ok_or(Malformed::Expected("a range like 5-8"))?;
+        Ok::<_, Malformed>(Span::new(from.parse()?, to.parse()?))
+    })?;
+    blacklist.sort_by(|a, b| Ord::cmp(&a.start, &b.start));
+    Ok(Firewall { blacklist })
 }
 
 impl Firewall {
@@ -70,12 +70,22 @@ impl Firewall {
 
 #[test]
 fn test_firewall_firstfree() {
-    assert_eq!(generate(include_str!("day20_example.txt")).first_free(), 3)
+    assert_eq!(
+        generate(include_str!("day20_example.txt"))
+            .unwrap()
+            .first_free(),
+        3
+    )
 }
 
 #[test]
 fn test_firewall_allfree() {
-    assert_eq!(generate(include_str!("day20_example.txt")).all_free(9), 2)
+    assert_eq!(
+        generate(include_str!("day20_example.txt"))
+            .unwrap()
+            .all_free(9),
+        2
+    )
 }
 
 #[aoc(day20, part1)]
@@ -87,3 +97,5 @@ fn solve(fw: &Firewall) -> u32 {
 fn solve2(fw: &Firewall) -> u32 {
     fw.all_free(u32::MAX)
 }
+
+crate::solution::solution!(Day20 = 20, Firewall, generate, solve, solve2);
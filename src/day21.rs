@@ -3,6 +3,11 @@ use std::{num::ParseIntError, str::FromStr};
 use itertools::Itertools;
 use thiserror::Error;
 
+use crate::input::{parse_lines, InputError};
+
+// Both passwords are this long, which is as far as positions can go
+const PASSWORD_LEN: usize = 8;
+
 #[derive(Debug, Clone, Copy)]
 enum Instr {
     SwapPosition(usize, usize),
@@ -16,46 +21,85 @@ enum Instr {
 
 #[derive(Error, Debug)]
 enum InstrParseError {
-    #[error("Unrecognised instruction `{0}`")]
-    Unrecognised(String),
+    #[error("Unrecognised instruction")]
+    Unrecognised,
+
+    #[error("Expected a single letter, not `{0}`")]
+    Letter(String),
 
-    #[error("Number Parse")]
+    #[error("Number Parse: {0}")]
     NumberParse(#[from] ParseIntError),
+
+    #[error("Expected a position below {PASSWORD_LEN}, not {0}")]
+    Position(usize),
+
+    #[error("Expected one of the password's letters, not `{0}`")]
+    NotInPassword(char),
+
+    #[error("Expected the span to be reversed to start before it ends")]
+    Backwards,
 }
 
 impl FromStr for Instr {
     type Err = InstrParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let letter = |tok: &str| {
+            tok.chars()
+                .exactly_one()
+                .map_err(|_| InstrParseError::Letter(tok.to_string()))
+        };
         let toks = s.split_ascii_whitespace().collect_vec();
-        match (toks[0], toks[1]) {
-            ("swap", "position") => Ok(Instr::SwapPosition(toks[2].parse()?, toks[5].parse()?)),
-            ("swap", "letter") => Ok(Instr::SwapLetter(
-                toks[2].chars().next().unwrap(),
-                toks[5].chars().next().unwrap(),
-            )),
-            ("rotate", "based") => Ok(Instr::RotateByLetter(toks[6].chars().next().unwrap())),
-            ("rotate", "left") => Ok(Instr::RotateLeft(toks[2].parse()?)),
-            ("rotate", "right") => Ok(Instr::RotateRight(toks[2].parse()?)),
-            ("reverse", _) => Ok(Instr::Reverse(toks[2].parse()?, toks[4].parse()?)),
-            ("move", _) => Ok(Instr::Move(toks[2].parse()?, toks[5].parse()?)),
-            _ => Err(InstrParseError::Unrecognised(s.to_string())),
+        match toks[..] {
+            ["swap", "position", x, _, _, y] => Ok(Instr::SwapPosition(x.parse()?, y.parse()?)),
+            ["swap", "letter", x, _, _, y] => Ok(Instr::SwapLetter(letter(x)?, letter(y)?)),
+            ["rotate", "based", _, _, _, _, x] => Ok(Instr::RotateByLetter(letter(x)?)),
+            ["rotate", "left", n, _] => Ok(Instr::RotateLeft(n.parse()?)),
+            ["rotate", "right", n, _] => Ok(Instr::RotateRight(n.parse()?)),
+            ["reverse", _, x, _, y] => Ok(Instr::Reverse(x.parse()?, y.parse()?)),
+            ["move", _, x, _, _, y] => Ok(Instr::Move(x.parse()?, y.parse()?)),
+            _ => Err(InstrParseError::Unrecognised),
         }
     }
 }
 
-struct Scrambler {
+impl Instr {
+    // Passwords are the first few letters of the alphabet, and anything
+    // that reaches past them can't be scrambled
+    fn check(self) -> Result<Self, InstrParseError> {
+        let position = |p| match p < PASSWORD_LEN {
+            true => Ok(()),
+            false => Err(InstrParseError::Position(p)),
+        };
+        let letter = |c: char| match ('a'..).take(PASSWORD_LEN).contains(&c) {
+            true => Ok(()),
+            false => Err(InstrParseError::NotInPassword(c)),
+        };
+        match self {
+            Instr::SwapPosition(x, y) | Instr::Move(x, y) => position(x).and(position(y))?,
+            Instr::Reverse(x, y) if x > y => Err(InstrParseError::Backwards)?,
+            Instr::Reverse(_, y) => position(y)?,
+            Instr::SwapLetter(x, y) => letter(x).and(letter(y))?,
+            Instr::RotateByLetter(x) => letter(x)?,
+            Instr::RotateLeft(_) | Instr::RotateRight(_) => (),
+        }
+        Ok(self)
+    }
+}
+
+pub struct Scrambler {
     program: Vec<Instr>,
 }
 
 impl Scrambler {
-    fn new(from: &str) -> Self {
-        let program = from.lines().map(|l| l.parse().unwrap()).collect();
-        Self { program }
+    fn new(from: &str) -> Result<Self, InputError> {
+        let program = parse_lines(21, from, |line| line.parse::<Instr>()?.check())?;
+        Ok(Self { program })
     }
 
     fn scramble(&self, s: &str) -> String {
         let mut bytes = s.as_bytes().to_vec();
+        let len = bytes.len();
         self.program.iter().for_each(|&instr| match instr {
             Instr::SwapPosition(x, y) => (bytes[x], bytes[y]) = (bytes[y], bytes[x]),
             Instr::SwapLetter(x, y) => bytes.iter_mut().for_each(|c| {
@@ -65,8 +109,8 @@ impl Scrambler {
                     *c = x as u8
                 }
             }),
-            Instr::RotateLeft(distance) => bytes.rotate_left(distance),
-            Instr::RotateRight(distance) => bytes.rotate_right(distance),
+            Instr::RotateLeft(distance) => bytes.rotate_left(distance % len),
+            Instr::RotateRight(distance) => bytes.rotate_right(distance % len),
             Instr::RotateByLetter(c) => {
                 if let Some(index) = bytes.iter().position(|b| *b == c as u8) {
                     let distance = if index >= 4 { index + 2 } else { index + 1 };
@@ -86,7 +130,6 @@ impl Scrambler {
     fn unscramble(&self, s: &str) -> String {
         let cracked = s
             .chars()
-            .into_iter()
             .permutations(s.len())
             .find(|v| self.scramble(&String::from_iter(v)) == s)
             .unwrap();
@@ -98,13 +141,15 @@ impl Scrambler {
 #[test]
 fn test_scramble() {
     assert_eq!(
-        generate(include_str!("day21_example.txt")).scramble("abcde"),
+        generate(include_str!("day21_example.txt"))
+            .unwrap()
+            .scramble("abcde"),
         "decab"
     );
 }
 
 #[aoc_generator(day21)]
-fn generate(input: &str) -> Scrambler {
+fn generate(input: &str) -> Result<Scrambler, InputError> {
     Scrambler::new(input)
 }
 
@@ -117,3 +162,5 @@ fn solve(scrambler: &Scrambler) -> String {
 fn solve2(scrambler: &Scrambler) -> String {
     scrambler.unscramble("fbgdceah")
 }
+
+crate::solution::solution!(Day21 = 21, Scrambler, generate, solve, solve2);
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
-use thiserror::Error;
+
+use crate::input::{InputError, Malformed};
 
 #[derive(PartialEq, Clone, Copy)]
-struct Node {
+pub struct Node {
     x: usize,
     y: usize,
     used: usize,
@@ -23,31 +24,46 @@ impl Node {
     }
 }
 
-#[derive(Debug, Error)]
-enum NodeParseError {
-    #[error("Parse failed")]
-    ParseInt(#[from] std::num::ParseIntError),
-}
-
 impl std::str::FromStr for Node {
-    type Err = NodeParseError;
+    type Err = Malformed;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // Filesystem              Size  Used  Avail  Use%
         // /dev/grid/node-x0-y0     93T   67T    26T   72%
         let toks = s.split_ascii_whitespace().collect_vec();
-        let path = toks[0].split('-').collect_vec();
-        let x = path[1][1..].parse()?;
-        let y = path[2][1..].parse()?;
-        let used = toks[2][..toks[2].len() - 1].parse()?;
-        let avail = toks[3][..toks[3].len() - 1].parse()?;
-        Ok(Node { x, y, used, avail })
+        let [path, _, used, avail, _] = toks[..] else {
+            return Err(Malformed::Expected("a node, its size and usage"));
+        };
+        let position = path
+            .split('-')
+            .skip(1)
+            .collect_tuple()
+            .and_then(|(x, y)| Some((x.strip_prefix('x')?, y.strip_prefix('y')?)));
+        let (x, y) = position.ok_or(Malformed::Expected("a node named like node-x0-y0"))?;
+        let terabytes = |size: &str| -> Result<usize, Malformed> {
+            let size = size.strip_suffix('T');
+            Ok(size.ok_or(Malformed::Expected("a size in T"))?.parse()?)
+        };
+        Ok(Node {
+            x: x.parse()?,
+            y: y.parse()?,
+            used: terabytes(used)?,
+            avail: terabytes(avail)?,
+        })
     }
 }
 
 #[aoc_generator(day22)]
-fn generate(s: &str) -> Vec<Node> {
-    s.lines().skip(2).map(|s| s.parse().unwrap()).collect()
+fn generate(s: &str) -> Result<Vec<Node>, InputError> {
+    // After the `df` command and its header
+    s.lines()
+        .enumerate()
+        .skip(2)
+        .map(|(i, line)| {
+            line.parse()
+                .map_err(|e| InputError::new(22, i + 1, line, e))
+        })
+        .collect()
 }
 
 #[aoc(day22, part1)]
@@ -60,27 +76,23 @@ fn solve(nodes: &[Node]) -> usize {
 }
 
 #[aoc(day22, part2, drawing)]
-fn draw_maze(nodes: &[Node]) -> String {
+pub(crate) fn draw_maze(nodes: &[Node]) -> String {
     let max_x = nodes.iter().map(|n| n.x).max().unwrap();
     let max_y = nodes.iter().map(|n| n.y).max().unwrap();
     let set: HashMap<(usize, usize), Node> =
         HashMap::from_iter(nodes.iter().map(|n| ((n.x, n.y), *n)));
 
-    for y in 0..=max_y {
-        for x in 0..=max_x {
-            if let Some(node) = set.get(&(x, y)) {
-                print!("{}", node.describe())
-            } else {
-                print!(" ")
-            }
-        }
-        println!();
-    }
-    String::from("")
+    (0..=max_y)
+        .map(|y| {
+            (0..=max_x)
+                .map(|x| set.get(&(x, y)).map_or(' ', |node| node.describe()))
+                .collect::<String>()
+        })
+        .join("\n")
 }
 
 #[aoc(day22, part2, solving)]
-fn do_math(nodes: &[Node]) -> usize {
+pub(crate) fn do_math(nodes: &[Node]) -> usize {
     let max_x = nodes.iter().map(|n| n.x).max().unwrap();
     let empty = nodes.iter().find(|n| n.used == 0).unwrap();
     let wall = nodes
@@ -99,3 +111,5 @@ fn do_math(nodes: &[Node]) -> usize {
     let home = max_x * 5 - 1;
     left + up + right + home
 }
+
+crate::solution::solution!(Day22 = 22, Vec<Node>, generate, solve, do_math);
@@ -1,22 +1,29 @@
 use crate::assembunny::*;
+use crate::input::{parse_program, InputError};
 
 #[aoc_generator(day23)]
-fn generate(input: &str) -> Vec<Instruction> {
-    input.lines().map(|l| l.parse().unwrap()).collect()
+fn generate(input: &str) -> Result<Vec<Instruction>, InputError> {
+    parse_program(23, input)
 }
 
 #[aoc(day23, part1)]
-fn solve(program: &[Instruction]) -> i32 {
+fn solve(program: &[Instruction]) -> i64 {
     let mut cpu = Cpu::new(program.to_vec());
-    cpu.set('a', 7);
+    cpu.optimize();
+    cpu.set_arithmetic(Arithmetic::I64);
+    cpu.set('a', 7).unwrap();
     cpu.run();
-    cpu.get('a')
+    cpu.get('a').unwrap()
 }
 
 #[aoc(day23, part2)]
-fn solve2(program: &[Instruction]) -> i32 {
+fn solve2(program: &[Instruction]) -> i64 {
     let mut cpu = Cpu::new(program.to_vec());
-    cpu.set('a', 12);
+    cpu.optimize();
+    cpu.set_arithmetic(Arithmetic::I64);
+    cpu.set('a', 12).unwrap();
     cpu.run();
-    cpu.get('a')
+    cpu.get('a').unwrap()
 }
+
+crate::solution::solution!(Day23 = 23, Vec<Instruction>, generate, solve, solve2);
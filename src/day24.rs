@@ -4,36 +4,84 @@ use itertools::Itertools;
 use ndarray::prelude::*;
 use pathfinding::prelude::dijkstra;
 
+use crate::input::InputError;
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 struct Point(i32, i32);
 
 #[derive(Debug)]
-struct Hvac {
+pub struct Hvac {
     map: Array2<u8>,
+    // Shortest distance between each pair of numbered locations, both ways
+    routes: HashMap<(Point, Point), usize>,
 }
 
 impl Hvac {
-    fn new(input: &str) -> Self {
-        let rows = input.lines().collect_vec().len();
-        let cols = input.lines().next().unwrap().len();
-        let data = input
-            .chars()
-            .filter(|c| *c != '\n')
-            .map(|c| c as u8)
-            .collect();
+    fn new(input: &str) -> Result<Self, InputError> {
+        let lines = input.lines().collect_vec();
+        let rows = lines.len();
+        let cols = lines.first().map_or(0, |l| l.len());
+        for (i, line) in lines.iter().enumerate() {
+            if line.len() != cols {
+                let reason = "expected every row the same width";
+                return Err(InputError::new(24, i + 1, line, reason));
+            }
+        }
+        if !input.contains('0') {
+            let first = lines.first().copied().unwrap_or_default();
+            return Err(InputError::new(
+                24,
+                1,
+                first,
+                "expected a location 0 to start from",
+            ));
+        }
 
+        let data = lines.concat().into_bytes();
         let map = Array::from_shape_vec((rows, cols), data)
             .unwrap()
             .reversed_axes();
 
-        Hvac { map }
+        let mut hvac = Hvac {
+            map,
+            routes: HashMap::new(),
+        };
+        hvac.routes = hvac.routes().map_err(|Point(x, y)| {
+            let (line, location) = (y as usize, hvac.map[[x as usize, y as usize]]);
+            let reason = format!("location {} unreachable", location as char);
+            InputError::new(24, line + 1, lines[line], reason)
+        })?;
+        Ok(hvac)
+    }
+
+    // Fails with any location that can't be reached from the first
+    fn routes(&self) -> Result<HashMap<(Point, Point), usize>, Point> {
+        let mut routes = HashMap::new();
+        for (start, end) in self.nodes().into_iter().tuple_combinations() {
+            let (_, cost) = dijkstra(
+                &start,
+                |p| self.successors(p).into_iter().map(|s| (s, 1)).collect_vec(),
+                |p| *p == end,
+            )
+            .ok_or(end)?;
+            routes.insert((start, end), cost);
+            routes.insert((end, start), cost);
+        }
+        Ok(routes)
     }
 
     fn successors(&self, from: &Point) -> Vec<Point> {
         [(-1, 0), (1, 0), (0, 1), (0, -1)]
             .iter()
             .map(|(xp, yp)| Point(from.0 + xp, from.1 + yp))
-            .filter(|p| self.map[[p.0 as usize, p.1 as usize]] != b'#')
+            .filter(|p| {
+                // Off the edge of the map is as good as a wall
+                let cell = usize::try_from(p.0)
+                    .ok()
+                    .zip(usize::try_from(p.1).ok())
+                    .and_then(|(x, y)| self.map.get([x, y]));
+                cell.is_some_and(|&c| c != b'#')
+            })
             .collect_vec()
     }
 
@@ -54,32 +102,13 @@ impl Hvac {
 }
 
 #[aoc_generator(day24)]
-fn generate(s: &str) -> Hvac {
+fn generate(s: &str) -> Result<Hvac, InputError> {
     Hvac::new(s)
 }
 
 fn solver(hvac: &Hvac, back_home: bool) -> usize {
     let nodes = hvac.nodes();
-    let routes: HashMap<(Point, Point), usize> = HashMap::from_iter(
-        nodes
-            .iter()
-            .permutations(2)
-            .map(|v| (v[0], v[1]))
-            .filter(|(start, end)| start != end)
-            .filter_map(|(start, end)| {
-                dijkstra(
-                    start,
-                    |p| hvac.successors(p).into_iter().map(|s| (s, 1)).collect_vec(),
-                    |p| *p == *end,
-                )
-            })
-            .flat_map(|(path, cost)| {
-                [
-                    ((*path.first().unwrap(), *path.last().unwrap()), cost),
-                    ((*path.last().unwrap(), *path.first().unwrap()), cost),
-                ]
-            }),
-    );
+    let routes = &hvac.routes;
 
     let start = nodes[0];
     let rest = &nodes[1..];
@@ -113,5 +142,17 @@ fn solve2(hvac: &Hvac) -> usize {
 
 #[test]
 fn test_solve() {
-    assert_eq!(solve(&generate(include_str!("day24_example.txt"))), 14);
+    assert_eq!(
+        solve(&generate(include_str!("day24_example.txt")).unwrap()),
+        14
+    );
+}
+
+#[test]
+fn test_unreachable() {
+    let error = generate("#####\n#0#1#\n#####").unwrap_err();
+    assert_eq!((error.line, error.text.as_str()), (2, "#0#1#"));
+    assert_eq!(error.reason, "location 1 unreachable");
 }
+
+crate::solution::solution!(Day24 = 24, Hvac, generate, solve, solve2);
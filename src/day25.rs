@@ -1,20 +1,60 @@
 use itertools::Itertools;
 
 use crate::assembunny::*;
+use crate::input::{parse_program, InputError};
 
 #[aoc_generator(day25)]
-fn generate(input: &str) -> Vec<Instruction> {
-    input.lines().map(|l| l.parse().unwrap()).collect()
+fn generate(input: &str) -> Result<Vec<Instruction>, InputError> {
+    parse_program(25, input)
 }
 
 #[aoc(day25, part1)]
-fn solve(program: &[Instruction]) -> i32 {
+fn solve(program: &[Instruction]) -> i64 {
     (0..)
         .into_iter()
         .find(|&int| {
             let mut cpu = Cpu::new(program.to_vec());
-            cpu.set('a', int);
-            cpu.iter().take(1000).tuple_windows().all(|(a, b)| a != b)
+            cpu.set('a', int).unwrap();
+            // Proven periodic, and alternating across the wrap-around too
+            cpu.find_cycle(1_000_000).is_some_and(|cycle| {
+                cycle
+                    .iter()
+                    .take(cycle.prefix.len() + 2 * cycle.repeat.len())
+                    .tuple_windows()
+                    .all(|(a, b)| a != b)
+            })
         })
         .unwrap()
 }
+
+// Check a good stretch of output alternates, giving up on each `a` at the
+// first wrong bit rather than proving it periodic
+#[aoc(day25, part1, pattern)]
+pub(crate) fn solve_pattern(program: &[Instruction]) -> i64 {
+    (0..)
+        .find(|&int| {
+            let mut cpu = Cpu::new(program.to_vec());
+            cpu.set('a', int).unwrap();
+            let mut pattern = Pattern::new(&[0, 1], 100);
+            cpu.run_with_io(&mut pattern, &mut std::iter::empty(), &mut 1_000_000);
+            pattern.complete()
+        })
+        .unwrap()
+}
+
+// Work out the output as a function of `a` rather than trying each in turn
+#[aoc(day25, part1, symbolic)]
+pub(crate) fn solve_symbolic(program: &[Instruction]) -> i64 {
+    analyze_clock(program, 1_000_000)
+        .unwrap()
+        .smallest_clock()
+        .unwrap()
+}
+
+#[test]
+fn test_variants_agree() {
+    let program = generate(include_str!("../input/2016/day25.txt")).unwrap();
+    assert_eq!(solve_pattern(&program), solve_symbolic(&program));
+}
+
+crate::solution::solution!(Day25 = 25, Vec<Instruction>, generate, solve);
@@ -0,0 +1,125 @@
+// Puzzle inputs that don't parse.  Generators report the first bad line, or
+// every one where they can find them all, so a damaged input file says where
+// the damage is rather than panicking.
+use std::fmt::Display;
+use std::num::ParseIntError;
+
+use thiserror::Error;
+
+use crate::assembunny::{Instruction, Program, ProgramParseError};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("day{day} line {line}: {reason}: `{text}`{}", .more.iter().map(|e| format!("\n{e}")).collect::<String>())]
+pub struct InputError {
+    pub day: u32,
+    // Counting from 1
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+    // Any bad lines after this one
+    pub more: Vec<InputError>,
+}
+
+impl InputError {
+    pub fn new(day: u32, line: usize, text: &str, reason: impl Display) -> Self {
+        InputError {
+            day,
+            line,
+            text: text.to_string(),
+            reason: reason.to_string(),
+            more: vec![],
+        }
+    }
+}
+
+// Why one line didn't parse, for days without anything more specific
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Malformed {
+    #[error("expected {0}")]
+    Expected(&'static str),
+
+    #[error("bad number: {0}")]
+    Number(#[from] ParseIntError),
+}
+
+// Parse each line in turn, stopping at the first that won't
+pub fn parse_lines<T, E: Display>(
+    day: u32,
+    input: &str,
+    mut parse: impl FnMut(&str) -> Result<T, E>,
+) -> Result<Vec<T>, InputError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| parse(text).map_err(|e| InputError::new(day, i + 1, text, e)))
+        .collect()
+}
+
+// An assembunny listing, for days 12, 23 and 25, with every bad line in it
+pub fn parse_program(day: u32, input: &str) -> Result<Vec<Instruction>, InputError> {
+    Program::parse(input).map_err(|ProgramParseError(errors)| {
+        let mut errors = errors.iter().map(|e| {
+            let text = input.lines().nth(e.line - 1).unwrap_or_default();
+            InputError::new(day, e.line, text, &e.error)
+        });
+        let mut first = errors.next().expect("a parse error has a bad line");
+        first.more = errors.collect();
+        first
+    })
+}
+
+#[test]
+fn test_parse_lines() {
+    let numbers = parse_lines(1, "1\n2\n3", str::parse::<u32>);
+    assert_eq!(numbers, Ok(vec![1, 2, 3]));
+
+    let error = parse_lines(1, "1\ntwo\n3", str::parse::<u32>).unwrap_err();
+    assert_eq!((error.line, error.text.as_str()), (2, "two"));
+    assert_eq!(
+        error.to_string(),
+        "day1 line 2: invalid digit found in string: `two`"
+    );
+
+    let error = parse_program(12, "inc a\ninc e\ncpy 1\njnz a 2").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "day12 line 2: unknown register `e`: `inc e`\n\
+         day12 line 3: `cpy` is missing an operand: `cpy 1`"
+    );
+}
+
+#[test]
+fn test_bad_inputs() {
+    let cases = [
+        (1, "R2, L3, X5", 1),
+        (3, "5 10 25\n3 4", 2),
+        (7, "abba[mnop]qrst\nioxxoj[asdfgh", 2),
+        (9, "A(1x5)BC\nX(8x2)(3x3)AB", 2),
+        (11, "The first floor contains nothing relevant.\nnothing", 2),
+        (4, "aaaaa-bbb-z-y-x-123[abxyz]\nnot-a-room", 2),
+        (8, "rect 3x2\nrotate row y=9 by 1", 2),
+        (10, "value 5 goes to bot 2\nbot 2 gives low to bin 1 and high to bot 0", 2),
+        (12, "cpy 41 a\ninc a\njnz a", 3),
+        (13, "ten", 1),
+        (15, "Disc #1 has 5 positions; at time=0, it is at position x.", 1),
+        (19, "", 1),
+        (20, "5-8\n0-2\n4_7", 3),
+        (21, "swap position 4 with position 0\nswap letter dd with letter b", 2),
+        (21, "swap position 4 with position 0\nswap position 9 with position 0", 2),
+        (21, "reverse positions 0 through 4\nswap letter z with letter b", 2),
+        (22, "root@ebhq-gridcenter# df -h\nFilesystem Size Used Avail Use%\n/dev/grid/node-x0 94T 65T 29T 69%", 3),
+        (24, "#####\n#0..#\n#..1\n#####", 3),
+    ];
+    for (day, input, line) in cases {
+        let error = crate::registry::variants()
+            .iter()
+            .find(|v| v.day == day)
+            .unwrap()
+            .parse(input)
+            .err()
+            .unwrap();
+        let error = error.downcast_ref::<InputError>().unwrap();
+        assert_eq!((error.day, error.line), (day, line), "{error}");
+        assert_eq!(error.text, input.lines().nth(line - 1).unwrap_or_default());
+    }
+}
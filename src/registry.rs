@@ -0,0 +1,296 @@
+// Every solution and its variants, so they can be listed and run without
+// going through cargo-aoc.
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::solution::Solution;
+use crate::*;
+
+// A day's parsed input, ready for any of its variants
+pub type Parsed = Box<dyn Any>;
+
+pub struct Variant {
+    pub day: u32,
+    pub part: u32,
+    // None for the main solution, otherwise the name given to `#[aoc]`
+    pub name: Option<&'static str>,
+    parse: fn(&str) -> Result<Parsed, Box<dyn Error>>,
+    solve: fn(&dyn Any) -> String,
+}
+
+impl Variant {
+    // Parse the input as read from a file, ready to solve
+    pub fn parse(&self, input: &str) -> Result<Parsed, Box<dyn Error>> {
+        (self.parse)(input.trim_end_matches('\n'))
+    }
+
+    // Panics if `input` was parsed for some other day
+    pub fn solve(&self, input: &Parsed) -> String {
+        (self.solve)(input.as_ref())
+    }
+
+    pub fn run(&self, input: &str) -> Result<String, Box<dyn Error>> {
+        Ok(self.solve(&self.parse(input)?))
+    }
+
+    // Takes more than a few seconds, even in a release build
+    pub fn slow(&self) -> bool {
+        matches!(
+            (self.day, self.part, self.name),
+            (5, _, None) | (11, _, None) | (14, 2, None)
+        )
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day{} part{}", self.day, self.part)?;
+        match self.name {
+            Some(name) => write!(f, " {name}"),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse<S: Solution>(input: &str) -> Result<Parsed, Box<dyn Error>>
+where
+    S::Input: 'static,
+{
+    Ok(Box::new(S::parse(input)?))
+}
+
+fn input<S: Solution>(parsed: &dyn Any) -> &S::Input
+where
+    S::Input: 'static,
+{
+    parsed.downcast_ref().expect("input parsed for another day")
+}
+
+fn part1<S: Solution>(parsed: &dyn Any) -> String
+where
+    S::Input: 'static,
+{
+    S::part1(input::<S>(parsed))
+}
+
+fn part2<S: Solution>(parsed: &dyn Any) -> String
+where
+    S::Input: 'static,
+{
+    S::part2(input::<S>(parsed)).expect("no second part")
+}
+
+macro_rules! variant {
+    ($solution:ty, 1) => {
+        Variant {
+            day: <$solution>::DAY,
+            part: 1,
+            name: None,
+            parse: parse::<$solution>,
+            solve: part1::<$solution>,
+        }
+    };
+    ($solution:ty, 2) => {
+        Variant {
+            day: <$solution>::DAY,
+            part: 2,
+            name: None,
+            parse: parse::<$solution>,
+            solve: part2::<$solution>,
+        }
+    };
+    ($solution:ty, $part:literal, $name:literal, $solve:path) => {
+        Variant {
+            day: <$solution>::DAY,
+            part: $part,
+            name: Some($name),
+            parse: parse::<$solution>,
+            solve: |parsed| $solve(input::<$solution>(parsed)).to_string(),
+        }
+    };
+}
+
+// By day and part, with the main solution ahead of any named variants
+static VARIANTS: &[Variant] = &[
+    variant!(day01::Day01, 1),
+    variant!(day01::Day01, 2),
+    variant!(day02::Day02, 1),
+    variant!(day02::Day02, 2),
+    variant!(day03::Day03, 1),
+    variant!(day03::Day03, 2),
+    variant!(day04::Day04, 1),
+    variant!(day04::Day04, 2),
+    variant!(day05::Day05, 1),
+    variant!(day05::Day05, 2),
+    variant!(day06::Day06, 1),
+    variant!(day06::Day06, 2),
+    variant!(day07::Day07, 1),
+    variant!(day07::Day07, 2),
+    variant!(day08::Day08, 1),
+    variant!(day08::Day08, 2),
+    variant!(day09::Day09, 1),
+    variant!(day09::Day09, 2),
+    variant!(day10::Day10, 1),
+    variant!(day10::Day10, 2),
+    variant!(day11::Day11, 1),
+    variant!(day11::Day11, 2),
+    variant!(day12::Day12, 1),
+    variant!(day12::Day12, 1, "compiled", day12::solve_compiled),
+    variant!(day12::Day12, 2),
+    variant!(day13::Day13, 1),
+    variant!(day13::Day13, 2),
+    variant!(day14::Day14, 1),
+    variant!(day14::Day14, 2),
+    variant!(day15::Day15, 1),
+    variant!(day15::Day15, 2),
+    variant!(day16::Day16, 1),
+    variant!(day16::Day16, 2),
+    variant!(day17::Day17, 1),
+    variant!(day17::Day17, 2),
+    variant!(day18::Day18, 1),
+    variant!(day18::Day18, 2),
+    variant!(day19::Day19, 1, "bitmath", day19::josephus_bitmath),
+    variant!(day19::Day19, 1, "rotate", day19::josephus_fiddling),
+    variant!(day19::Day19, 1, "slice", day19::josephus),
+    variant!(day19::Day19, 2),
+    variant!(day20::Day20, 1),
+    variant!(day20::Day20, 2),
+    variant!(day21::Day21, 1),
+    variant!(day21::Day21, 2),
+    variant!(day22::Day22, 1),
+    variant!(day22::Day22, 2, "drawing", day22::draw_maze),
+    variant!(day22::Day22, 2, "solving", day22::do_math),
+    variant!(day23::Day23, 1),
+    variant!(day23::Day23, 2),
+    variant!(day24::Day24, 1),
+    variant!(day24::Day24, 2),
+    variant!(day25::Day25, 1),
+    variant!(day25::Day25, 1, "pattern", day25::solve_pattern),
+    variant!(day25::Day25, 1, "symbolic", day25::solve_symbolic),
+];
+
+pub fn variants() -> &'static [Variant] {
+    VARIANTS
+}
+
+// What to run for a day and part when no variant is named: the main solution
+// if there is one, otherwise every variant there is
+pub fn defaults(day: u32, part: u32) -> Vec<&'static Variant> {
+    let all = VARIANTS
+        .iter()
+        .filter(|v| v.day == day && v.part == part)
+        .collect::<Vec<_>>();
+    match all.iter().find(|v| v.name.is_none()) {
+        Some(&main) => vec![main],
+        None => all,
+    }
+}
+
+pub fn find(day: u32, part: u32, name: &str) -> Option<&'static Variant> {
+    VARIANTS
+        .iter()
+        .find(|v| v.day == day && v.part == part && v.name == Some(name))
+}
+
+// Known answers, one per line as `day19 part1 slice: 1815603`, keyed by the
+// variant's name.  Blank lines and `#` comments are skipped.
+pub fn parse_answers(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, answer)| (key.trim().to_string(), unescape(answer.trim_start())))
+        .collect()
+}
+
+// How an answer is written in the answers file
+pub fn escape(answer: &str) -> String {
+    answer.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(answer: &str) -> String {
+    let mut out = String::new();
+    let mut chars = answer.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => out.push('\n'),
+            ('\\', Some('\\')) => out.push('\\'),
+            _ => {
+                out.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+    out
+}
+
+#[test]
+fn test_answers() {
+    let answers = parse_answers(include_str!("../input/2016/answers.txt"));
+    let unanswered = variants()
+        .iter()
+        .filter(|v| !answers.contains_key(&v.to_string()))
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(unanswered, ["day11 part2"]);
+    assert!(answers
+        .keys()
+        .all(|key| variants().iter().any(|v| v.to_string() == *key)));
+
+    // The quick ones, even in a debug build
+    for variant in variants()
+        .iter()
+        .filter(|v| [1, 2, 6, 8, 12].contains(&v.day))
+    {
+        let input = std::fs::read_to_string(format!("input/2016/day{}.txt", variant.day)).unwrap();
+        assert_eq!(variant.run(&input).unwrap(), answers[&variant.to_string()]);
+    }
+
+    for answer in ["", "a\\nb", "\n#.\n.#", "\\"] {
+        assert_eq!(unescape(&escape(answer)), answer);
+    }
+}
+
+#[test]
+fn test_registry() {
+    assert_eq!(variants().len(), 55);
+    assert!((1..=25).all(|day| !defaults(day, 1).is_empty()));
+    assert!((1..=24).all(|day| !defaults(day, 2).is_empty()));
+    assert_eq!(defaults(19, 1).len(), 3);
+
+    let slice = find(19, 1, "slice").unwrap();
+    assert_eq!(slice.to_string(), "day19 part1 slice");
+    assert_eq!(slice.run("5").unwrap(), "3");
+    assert_eq!(
+        defaults(12, 1)[0]
+            .run(include_str!("day12_example.txt"))
+            .unwrap(),
+        "42"
+    );
+    assert!(find(12, 1, "nonsense").is_none());
+    assert_eq!(variants().iter().filter(|v| v.slow()).count(), 5);
+}
+
+// The registry is kept by hand, so check it against the `#[aoc]` solutions in
+// the day modules themselves
+#[test]
+fn test_registry_matches_days() {
+    let mut registered = variants().iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    let mut declared = vec![];
+    for day in 1..=25 {
+        let source = std::fs::read_to_string(format!("src/day{day:02}.rs")).unwrap();
+        for line in source.lines() {
+            if let Some(args) = line
+                .strip_prefix("#[aoc(")
+                .and_then(|l| l.strip_suffix(")]"))
+            {
+                declared.push(args.split(", ").collect::<Vec<_>>().join(" "));
+            }
+        }
+    }
+    registered.sort();
+    declared.sort();
+    assert_eq!(registered, declared);
+}
@@ -0,0 +1,78 @@
+// A day's puzzle as a parse step and its two parts, so the solvers can be
+// used without aoc-runner.  Each day has one, `day01::Day01` to
+// `day25::Day25`, and the `registry` has them all, variants included.
+use std::convert::Infallible;
+use std::error::Error;
+
+pub trait Solution {
+    const DAY: u32;
+    type Input;
+
+    // Takes the input with its trailing newline removed, as aoc-runner
+    // hands it over
+    fn parse(input: &str) -> Result<Self::Input, Box<dyn Error>>;
+
+    fn part1(input: &Self::Input) -> String;
+
+    // None on the last day, which only has the one puzzle
+    fn part2(_input: &Self::Input) -> Option<String> {
+        None
+    }
+
+    // Parse input straight from a file
+    fn load(input: &str) -> Result<Self::Input, Box<dyn Error>> {
+        Self::parse(input.trim_end_matches('\n'))
+    }
+}
+
+// Declares `DayNN` and its `Solution`, from the day's generator and the main
+// function for each part, all as they're given to aoc-runner
+//
+//   solution!(Day12 = 12, Vec<Instruction>, generate, solve, solve2);
+//
+macro_rules! solution {
+    ($name:ident = $day:literal, $input:ty, $parse:expr, $part1:expr $(, $part2:expr)?) => {
+        pub struct $name;
+
+        impl crate::Solution for $name {
+            const DAY: u32 = $day;
+            type Input = $input;
+
+            fn parse(input: &str) -> Result<Self::Input, Box<dyn std::error::Error>> {
+                Ok($parse(input)?)
+            }
+
+            fn part1(input: &Self::Input) -> String {
+                $part1(input).to_string()
+            }
+
+            $(
+                fn part2(input: &Self::Input) -> Option<String> {
+                    Some($part2(input).to_string())
+                }
+            )?
+        }
+    };
+}
+pub(crate) use solution;
+
+// The generator for days that take their input as it comes
+pub(crate) fn text(input: &str) -> Result<String, Infallible> {
+    Ok(input.to_string())
+}
+
+#[test]
+fn test_solution() {
+    use crate::{day12::Day12, day19::Day19, day25::Day25};
+
+    let players = Day19::load("5\n").unwrap();
+    assert_eq!(Day19::part1(&players), "3");
+    assert_eq!(Day19::part2(&players).unwrap(), "2");
+
+    let program = Day12::load(include_str!("day12_example.txt")).unwrap();
+    assert_eq!(Day12::part1(&program), "42");
+    assert!(Day12::parse("cpy 1").is_err());
+
+    let program = Day25::parse("out a").unwrap();
+    assert_eq!(Day25::part2(&program), None);
+}